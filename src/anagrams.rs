@@ -0,0 +1,150 @@
+use crate::{
+  arrangement::{distinct, Arrangements, Permutation},
+  try_count_multiplicities, AnagramOptions,
+};
+use std::iter::FusedIterator;
+
+/// Iterate over every distinct anagram of a word in lexicographic order,
+/// starting from its sorted form
+///
+/// Examples:
+/// "bca" -> "abc", "acb", "bac", "bca", "cab", "cba"
+/// "aab" -> "aab", "aba", "baa"
+pub fn anagrams(word: &str) -> Anagrams {
+  Anagrams::new(&AnagramOptions::new().units(word))
+}
+
+/// Iterate over every distinct anagram of a word after normalizing it with the
+/// given options, permuting grapheme clusters if the options ask for them
+pub fn anagrams_with(word: &str, options: &AnagramOptions) -> Anagrams {
+  Anagrams::new(&options.units(&options.normalize(word)))
+}
+
+/// A lazy iterator over the distinct anagrams of a word, created by
+/// [`anagrams`]
+#[derive(Debug, Clone)]
+pub struct Anagrams {
+  arrangements: Arrangements<Permutation>,
+  remaining:    Option<u128>,
+}
+
+impl Anagrams {
+  fn new(units: &[&str]) -> Self {
+    let (distinct, indices) = distinct(units);
+
+    let mut counts = vec![0; distinct.len()];
+    for &i in &indices {
+      counts[i] += 1;
+    }
+
+    Self {
+      remaining:    try_count_multiplicities(&counts),
+      arrangements: Arrangements::new(distinct, Some(Permutation::new(indices))),
+    }
+  }
+
+  /// Write the next anagram into `buffer`, reusing its allocation, and
+  /// return `false` once every anagram has been produced
+  pub fn next_into(&mut self, buffer: &mut String) -> bool {
+    let more = self.arrangements.next_into(buffer);
+    self.produced(more);
+    more
+  }
+
+  /// Count down the anagrams left after trying to produce one
+  fn produced(&mut self, more: bool) {
+    self.remaining = if more {
+      self.remaining.map(|n| n - 1)
+    } else {
+      Some(0)
+    };
+  }
+}
+
+impl Iterator for Anagrams {
+  type Item = String;
+
+  fn next(&mut self) -> Option<String> {
+    let anagram = self.arrangements.next_string();
+    self.produced(anagram.is_some());
+    anagram
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    match self.remaining {
+      Some(n) if n <= usize::MAX as u128 => (n as usize, Some(n as usize)),
+      _ => (usize::MAX, None),
+    }
+  }
+}
+
+impl FusedIterator for Anagrams {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_anagrams() {
+    assert_eq!(anagrams("bca").collect::<Vec<_>>(), [
+      "abc", "acb", "bac", "bca", "cab", "cba"
+    ]);
+    assert_eq!(anagrams("aba").collect::<Vec<_>>(), ["aab", "aba", "baa"]);
+    assert_eq!(anagrams("aaa").collect::<Vec<_>>(), ["aaa"]);
+    assert_eq!(anagrams("").collect::<Vec<_>>(), [""]);
+    assert_eq!(anagrams("ordeals").count(), 5040);
+    assert_eq!(anagrams("mississippi").count(), 34650);
+  }
+
+  #[test]
+  fn test_anagrams_with() {
+    assert_eq!(
+      anagrams_with("B a!", &AnagramOptions::phrase()).collect::<Vec<_>>(),
+      ["ab", "ba"]
+    );
+  }
+
+  #[test]
+  fn test_anagrams_graphemes() {
+    let options = AnagramOptions::new().graphemes(true);
+    assert_eq!(
+      anagrams_with("👍🏽ae\u{301}", &options).collect::<Vec<_>>(),
+      [
+        "ae\u{301}👍🏽",
+        "a👍🏽e\u{301}",
+        "e\u{301}a👍🏽",
+        "e\u{301}👍🏽a",
+        "👍🏽ae\u{301}",
+        "👍🏽e\u{301}a"
+      ]
+    );
+    assert_eq!(anagrams("👍🏽").count(), 2);
+    assert_eq!(anagrams_with("👍🏽", &options).count(), 1);
+  }
+
+  #[test]
+  fn test_anagrams_size_hint() {
+    let mut iter = anagrams("aabc");
+    assert_eq!(iter.size_hint(), (12, Some(12)));
+    iter.next();
+    assert_eq!(iter.size_hint(), (11, Some(11)));
+    assert_eq!(iter.by_ref().count(), 11);
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert_eq!(
+      anagrams("abcdefghijklmnopqrstuvwxyzABCDEFGHI").size_hint(),
+      (usize::MAX, None)
+    );
+  }
+
+  #[test]
+  fn test_anagrams_next_into() {
+    let mut iter = anagrams("ba");
+    let mut buffer = String::new();
+    assert!(iter.next_into(&mut buffer));
+    assert_eq!(buffer, "ab");
+    assert!(iter.next_into(&mut buffer));
+    assert_eq!(buffer, "ba");
+    assert!(!iter.next_into(&mut buffer));
+    assert!(!iter.next_into(&mut buffer));
+  }
+}
@@ -0,0 +1,212 @@
+use crate::permutation::next_permutation;
+
+/// Split units into a sorted table of the distinct units and the position of
+/// each unit in that table, so arrangements only move integers around
+pub(crate) fn distinct(units: &[&str]) -> (Vec<String>, Vec<usize>) {
+  let mut distinct = units.to_vec();
+  distinct.sort_unstable();
+  distinct.dedup();
+
+  let indices = units
+    .iter()
+    .map(|unit| distinct.binary_search(unit).unwrap())
+    .collect();
+
+  (distinct.into_iter().map(String::from).collect(), indices)
+}
+
+/// Positions filled left to right with distinct units, which may refuse a
+/// unit that would leave the positions after it impossible to fill
+pub(crate) trait Place {
+  /// The number of positions left to fill
+  fn left(&self) -> usize;
+
+  /// Try to put a unit in the next position, returning `false` if it can't
+  /// go there
+  fn place(&mut self, unit: usize) -> bool;
+
+  /// Take a unit back out of the last filled position
+  fn unplace(&mut self, unit: usize);
+}
+
+/// Positions that take any unit as long as copies of it are left over
+#[derive(Debug, Clone)]
+pub(crate) struct Available {
+  available: Vec<usize>,
+  left:      usize,
+}
+
+impl Available {
+  /// Fill `k` positions from the given number of copies of each unit
+  pub(crate) fn new(available: Vec<usize>, k: usize) -> Self {
+    Self { available, left: k }
+  }
+}
+
+impl Place for Available {
+  fn left(&self) -> usize {
+    self.left
+  }
+
+  fn place(&mut self, unit: usize) -> bool {
+    if self.available[unit] == 0 {
+      return false;
+    }
+
+    self.available[unit] -= 1;
+    self.left -= 1;
+    true
+  }
+
+  fn unplace(&mut self, unit: usize) {
+    self.available[unit] += 1;
+    self.left += 1;
+  }
+}
+
+/// Steps through arrangements of distinct units in lexicographic order
+pub(crate) trait Step {
+  /// The current arrangement, as positions in the table of distinct units
+  fn current(&self) -> &[usize];
+
+  /// Step to the next arrangement, returning `false` if there is none
+  fn advance(&mut self) -> bool;
+}
+
+/// Every ordering of a fixed list of units, stepped through as permutations
+#[derive(Debug, Clone)]
+pub(crate) struct Permutation {
+  indices: Vec<usize>,
+}
+
+impl Permutation {
+  /// Start from the smallest ordering of the given units
+  pub(crate) fn new(mut indices: Vec<usize>) -> Self {
+    indices.sort_unstable();
+    Self { indices }
+  }
+}
+
+impl Step for Permutation {
+  fn current(&self) -> &[usize] {
+    &self.indices
+  }
+
+  fn advance(&mut self) -> bool {
+    next_permutation(&mut self.indices)
+  }
+}
+
+/// Every way to fill some positions, found by trying units in order and
+/// backtracking
+#[derive(Debug, Clone)]
+pub(crate) struct Backtrack<P> {
+  units:  usize,
+  place:  P,
+  chosen: Vec<usize>,
+}
+
+impl<P: Place> Backtrack<P> {
+  /// Start from the smallest way to fill the positions with `units` distinct
+  /// units, which must be possible
+  pub(crate) fn new(units: usize, place: P) -> Self {
+    let mut backtrack = Self {
+      chosen: Vec::with_capacity(place.left()),
+      units,
+      place,
+    };
+
+    backtrack.fill();
+    backtrack
+  }
+
+  /// Fill the positions left with the smallest units they take, which always
+  /// succeeds as long as units are only refused when the rest can't be filled
+  fn fill(&mut self) {
+    while self.place.left() > 0 {
+      let unit = (0..self.units)
+        .find(|&unit| self.place.place(unit))
+        .unwrap();
+      self.chosen.push(unit);
+    }
+  }
+}
+
+impl<P: Place> Step for Backtrack<P> {
+  fn current(&self) -> &[usize] {
+    &self.chosen
+  }
+
+  fn advance(&mut self) -> bool {
+    // give back units from the end until one can be swapped for a larger
+    // one, then complete the arrangement as small as possible
+    while let Some(unit) = self.chosen.pop() {
+      self.place.unplace(unit);
+
+      let larger = (unit + 1..self.units).find(|&i| self.place.place(i));
+
+      if let Some(larger) = larger {
+        self.chosen.push(larger);
+        self.fill();
+        return true;
+      }
+    }
+
+    false
+  }
+}
+
+/// Writes out each arrangement a step produces with a table of distinct
+/// units
+#[derive(Debug, Clone)]
+pub(crate) struct Arrangements<S> {
+  units:   Vec<String>,
+  step:    Option<S>,
+  started: bool,
+}
+
+impl<S: Step> Arrangements<S> {
+  /// Write out the arrangements of `step`, or nothing if it is `None`
+  pub(crate) fn new(units: Vec<String>, step: Option<S>) -> Self {
+    Self {
+      units,
+      step,
+      started: false,
+    }
+  }
+
+  /// Write the next arrangement into `buffer`, reusing its allocation, and
+  /// return `false` once every arrangement has been produced
+  pub(crate) fn next_into(&mut self, buffer: &mut String) -> bool {
+    let step = match &mut self.step {
+      Some(step) => step,
+      None => return false,
+    };
+
+    if self.started && !step.advance() {
+      self.step = None;
+      return false;
+    }
+
+    self.started = true;
+
+    buffer.clear();
+    for &i in step.current() {
+      buffer.push_str(&self.units[i]);
+    }
+
+    true
+  }
+
+  /// Get the next arrangement as a new string
+  pub(crate) fn next_string(&mut self) -> Option<String> {
+    let len = self.step.as_ref().map_or(0, |step| step.current().len());
+    let mut buffer = String::with_capacity(len);
+
+    if self.next_into(&mut buffer) {
+      Some(buffer)
+    } else {
+      None
+    }
+  }
+}
@@ -0,0 +1,214 @@
+//! Versions of the functions in this crate that can fail, returning an
+//! [`Error`] rather than panicking
+//!
+//! These cover the counting, ranking and unranking functions that panic when
+//! a count overflows or a rank is out of range. Empty strings, Unicode text,
+//! patterns longer than the text and lengths longer than the word are never
+//! errors anywhere in the crate
+
+use crate::{
+  rank::{try_unrank_big, try_unrank_big_with},
+  AnagramOptions, Error, Tokenizer, Wildcard,
+};
+use num_bigint::BigUint;
+use std::hash::Hash;
+
+/// Count the number of anagrams that can be formed from a word
+pub fn count(word: &str) -> Result<u128, Error> {
+  crate::try_count(word).ok_or(Error::Overflow)
+}
+
+/// Count the number of anagrams that can be formed from a word after
+/// normalizing it with the given options
+pub fn count_with(word: &str, options: &AnagramOptions) -> Result<u128, Error> {
+  crate::try_count_with(word, options).ok_or(Error::Overflow)
+}
+
+/// Count the distinct orderings of any items, where equal items are
+/// interchangeable
+pub fn count_arrangements<I, T>(items: I) -> Result<u128, Error>
+where
+  I: IntoIterator<Item = T>,
+  T: Hash + Eq,
+{
+  crate::try_count_arrangements(items).ok_or(Error::Overflow)
+}
+
+/// Count the distinct orderings of the words of a sentence
+pub fn count_word_orderings(text: &str, tokenizer: &Tokenizer) -> Result<u128, Error> {
+  crate::try_count_word_orderings(text, tokenizer).ok_or(Error::Overflow)
+}
+
+/// Count the distinct orderings of the words of a sentence after normalizing
+/// each word with the given options
+pub fn count_word_orderings_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> Result<u128, Error> {
+  crate::try_count_word_orderings_with(text, tokenizer, options).ok_or(Error::Overflow)
+}
+
+/// Count the distinct strings of `k` letters that can be formed from the
+/// letters of a word
+pub fn count_k(word: &str, k: usize) -> Result<u128, Error> {
+  crate::try_count_k(word, k).ok_or(Error::Overflow)
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word
+/// after normalizing it with the given options
+pub fn count_k_with(word: &str, k: usize, options: &AnagramOptions) -> Result<u128, Error> {
+  crate::try_count_k_with(word, k, options).ok_or(Error::Overflow)
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was
+pub fn count_derangements(word: &str) -> Result<u128, Error> {
+  crate::try_count_derangements(word).ok_or(Error::Overflow)
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was, after normalizing it with the given options
+pub fn count_derangements_with(word: &str, options: &AnagramOptions) -> Result<u128, Error> {
+  crate::try_count_derangements_with(word, options).ok_or(Error::Overflow)
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards
+pub fn count_wildcard(word: &str, wildcard: &Wildcard) -> Result<u128, Error> {
+  crate::try_count_wildcard(word, wildcard).ok_or(Error::Overflow)
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards after normalizing it with the given options
+pub fn count_wildcard_with(
+  word: &str,
+  wildcard: &Wildcard,
+  options: &AnagramOptions,
+) -> Result<u128, Error> {
+  crate::try_count_wildcard_with(word, wildcard, options).ok_or(Error::Overflow)
+}
+
+/// Get the position of a word among all of its anagrams in lexicographic
+/// order
+pub fn rank(word: &str) -> Result<u128, Error> {
+  crate::try_rank(word).ok_or(Error::Overflow)
+}
+
+/// Get the lexicographic position of a word among its anagrams after
+/// normalizing it with the given options
+pub fn rank_with(word: &str, options: &AnagramOptions) -> Result<u128, Error> {
+  crate::try_rank_with(word, options).ok_or(Error::Overflow)
+}
+
+/// Get the anagram of `letters` at position `n` in lexicographic order
+pub fn unrank(letters: &str, n: u128) -> Result<String, Error> {
+  unrank_big(letters, &BigUint::from(n))
+}
+
+/// Get the anagram at position `n` of `letters` after normalizing them with
+/// the given options
+pub fn unrank_with(letters: &str, n: u128, options: &AnagramOptions) -> Result<String, Error> {
+  unrank_big_with(letters, &BigUint::from(n), options)
+}
+
+/// Get the anagram of `letters` at position `n` in lexicographic order, for
+/// positions that do not fit in a `u128`
+pub fn unrank_big(letters: &str, n: &BigUint) -> Result<String, Error> {
+  try_unrank_big(letters, n)
+}
+
+/// Get the anagram at position `n` of `letters` after normalizing them with
+/// the given options, for positions that do not fit in a `u128`
+pub fn unrank_big_with(
+  letters: &str,
+  n: &BigUint,
+  options: &AnagramOptions,
+) -> Result<String, Error> {
+  try_unrank_big_with(letters, n, options)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LONG: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJ";
+
+  #[test]
+  fn test_count() {
+    assert_eq!(count("ordeals"), Ok(5040));
+    assert_eq!(count(""), Ok(1));
+    assert_eq!(count(LONG), Err(Error::Overflow));
+    assert_eq!(count_with("Aa", &AnagramOptions::phrase()), Ok(1));
+    assert_eq!(count_arrangements(vec![1, 1, 2]), Ok(3));
+    assert_eq!(count_arrangements(0..35), Err(Error::Overflow));
+    assert_eq!(
+      count_word_orderings("to be or not to be", &Tokenizer::Whitespace),
+      Ok(180)
+    );
+    assert_eq!(
+      count_word_orderings_with(
+        "To be or not to be",
+        &Tokenizer::Whitespace,
+        &AnagramOptions::phrase()
+      ),
+      Ok(180)
+    );
+    assert_eq!(count_k("aab", 2), Ok(3));
+    assert_eq!(count_k("aab", usize::MAX), Ok(0));
+    assert_eq!(count_derangements("aaab"), Ok(0));
+    assert_eq!(count_derangements(LONG), Err(Error::Overflow));
+    assert_eq!(
+      count_derangements_with("A a, B b", &AnagramOptions::phrase()),
+      Ok(1)
+    );
+    assert_eq!(count_k(LONG, 35), Err(Error::Overflow));
+    assert_eq!(count_k_with("AaB", 2, &AnagramOptions::phrase()), Ok(3));
+    assert_eq!(
+      count_wildcard(&"?".repeat(28), &Wildcard::new('?')),
+      Err(Error::Overflow)
+    );
+    assert_eq!(
+      count_wildcard_with("A?", &Wildcard::new('?'), &AnagramOptions::new()),
+      Ok(52)
+    );
+  }
+
+  #[test]
+  fn test_rank() {
+    assert_eq!(rank("cba"), Ok(5));
+    assert_eq!(rank(""), Ok(0));
+    assert_eq!(
+      rank(&LONG.chars().rev().collect::<String>()),
+      Err(Error::Overflow)
+    );
+    assert_eq!(rank_with("C b A", &AnagramOptions::phrase()), Ok(5));
+    assert_eq!(
+      rank_with("e\u{301}a", &AnagramOptions::new().graphemes(true)),
+      Ok(1)
+    );
+  }
+
+  #[test]
+  fn test_unrank() {
+    assert_eq!(unrank("cba", 5), Ok("cba".into()));
+    assert_eq!(unrank("", 0), Ok("".into()));
+    assert_eq!(
+      unrank("cba", 6),
+      Err(Error::RankOutOfRange {
+        rank:  BigUint::from(6u32),
+        count: BigUint::from(6u32),
+      })
+    );
+    assert_eq!(
+      unrank_with("B, a", 1, &AnagramOptions::phrase()),
+      Ok("ba".into())
+    );
+    assert_eq!(
+      unrank_with("e\u{301}a", 1, &AnagramOptions::new().graphemes(true)),
+      Ok("e\u{301}a".into())
+    );
+    assert!(unrank_big(LONG, &BigUint::from(u128::MAX)).is_ok());
+    assert!(unrank_big_with("ab", &BigUint::from(2u32), &AnagramOptions::new()).is_err());
+  }
+}
@@ -0,0 +1,173 @@
+use crate::{AnagramIndex, AnagramOptions};
+use std::io::{self, Write};
+
+/// A group of two or more words that are all anagrams of each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+  /// The signature every word in the cluster shares
+  pub signature: String,
+  /// The words in the cluster, in the order they were first seen
+  pub words:     Vec<String>,
+}
+
+/// A format clusters can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterFormat {
+  /// A JSON array of objects with a `signature` and a `words` array, one
+  /// object per line
+  Json,
+  /// One line per cluster, the signature followed by every word, separated
+  /// by tabs
+  Tsv,
+}
+
+/// Group a list of words into anagram classes, keeping only classes with two
+/// or more members, largest first
+///
+/// Examples:
+/// ["listen", "google", "silent", "enlist", "rat", "tar"]
+/// -> ["listen", "silent", "enlist"], ["rat", "tar"]
+pub fn clusters<I, S>(words: I) -> Vec<Cluster>
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  clusters_with(words, &AnagramOptions::new())
+}
+
+/// Group a list of words into anagram classes after normalizing them with the
+/// given options, keeping only classes with two or more members, largest
+/// first
+pub fn clusters_with<I, S>(words: I, options: &AnagramOptions) -> Vec<Cluster>
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  let mut index = AnagramIndex::with_options(options.clone());
+  index.extend(words);
+  index.clusters()
+}
+
+/// Write clusters in the given format
+pub fn write_clusters<W: Write>(
+  clusters: &[Cluster],
+  format: ClusterFormat,
+  mut writer: W,
+) -> io::Result<()> {
+  match format {
+    ClusterFormat::Json => {
+      write!(writer, "[")?;
+      for (i, cluster) in clusters.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        write!(
+          writer,
+          "{}\n  {{\"signature\": {}, \"words\": [",
+          separator,
+          quote(&cluster.signature)
+        )?;
+        for (j, word) in cluster.words.iter().enumerate() {
+          let separator = if j == 0 { "" } else { ", " };
+          write!(writer, "{}{}", separator, quote(word))?;
+        }
+        write!(writer, "]}}")?;
+      }
+      if !clusters.is_empty() {
+        writeln!(writer)?;
+      }
+      writeln!(writer, "]")
+    },
+    ClusterFormat::Tsv => {
+      for cluster in clusters {
+        write!(writer, "{}", escape(&cluster.signature))?;
+        for word in &cluster.words {
+          write!(writer, "\t{}", escape(word))?;
+        }
+        writeln!(writer)?;
+      }
+      Ok(())
+    },
+  }
+}
+
+/// Quote a string as a JSON string literal
+fn quote(text: &str) -> String {
+  let mut quoted = String::with_capacity(text.len() + 2);
+  quoted.push('"');
+  for c in text.chars() {
+    match c {
+      '"' => quoted.push_str("\\\""),
+      '\\' => quoted.push_str("\\\\"),
+      '\n' => quoted.push_str("\\n"),
+      '\r' => quoted.push_str("\\r"),
+      '\t' => quoted.push_str("\\t"),
+      c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+      c => quoted.push(c),
+    }
+  }
+  quoted.push('"');
+  quoted
+}
+
+/// Escape the chars that would break a TSV field
+fn escape(field: &str) -> String {
+  field
+    .replace('\\', "\\\\")
+    .replace('\t', "\\t")
+    .replace('\n', "\\n")
+    .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_clusters() {
+    let found = clusters([
+      "rat", "listen", "google", "silent", "tar", "enlist", "art", "stone", "notes", "onset",
+      "tones", "Rat",
+    ]);
+
+    let words: Vec<_> = found.iter().map(|cluster| cluster.words.clone()).collect();
+    assert_eq!(words, [
+      vec!["stone", "notes", "onset", "tones"],
+      vec!["rat", "tar", "art"],
+      vec!["listen", "silent", "enlist"],
+    ]);
+    assert_eq!(found[1].signature, "art");
+
+    assert!(clusters(["a", "b", "a"]).is_empty());
+
+    let found = clusters_with(["Rat", "tar", "TAR"], &AnagramOptions::phrase());
+    assert_eq!(found[0].words, ["Rat", "tar", "TAR"]);
+  }
+
+  #[test]
+  fn test_write_clusters() {
+    let found = clusters(["rat", "tar", "a\tb", "b\ta"]);
+
+    let mut tsv = Vec::new();
+    write_clusters(&found, ClusterFormat::Tsv, &mut tsv).unwrap();
+    assert_eq!(
+      String::from_utf8(tsv).unwrap(),
+      "art\trat\ttar\n\\tab\ta\\tb\tb\\ta\n"
+    );
+
+    let mut json = Vec::new();
+    write_clusters(&found, ClusterFormat::Json, &mut json).unwrap();
+    assert_eq!(
+      String::from_utf8(json).unwrap(),
+      concat!(
+        "[\n",
+        "  {\"signature\": \"art\", \"words\": [\"rat\", \"tar\"]},\n",
+        "  {\"signature\": \"\\tab\", \"words\": [\"a\\tb\", \"b\\ta\"]}\n",
+        "]\n"
+      )
+    );
+
+    let mut json = Vec::new();
+    write_clusters(&[], ClusterFormat::Json, &mut json).unwrap();
+    assert_eq!(json, b"[]\n");
+    assert_eq!(quote("\"\\\u{1}é"), "\"\\\"\\\\\\u0001é\"");
+  }
+}
@@ -0,0 +1,367 @@
+use crate::{
+  arrangement::{distinct, Arrangements, Backtrack, Place},
+  multinomial_big, AnagramOptions,
+};
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::{ToPrimitive, Zero};
+use rand::Rng;
+use std::iter::FusedIterator;
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was, treating repeated letters as interchangeable
+///
+/// Panics if the count does not fit in a `u128`, see
+/// `try_count_derangements` and `count_derangements_big` for long words
+/// Examples:
+/// "abc" -> 2, "bca" and "cab"
+/// "aabb" -> 1, "bbaa"
+/// "aaab" -> 0
+pub fn count_derangements(word: &str) -> u128 {
+  try_count_derangements(word)
+    .expect("derangement count overflowed u128, use `count_derangements_big` instead")
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was, after normalizing it with the given options
+pub fn count_derangements_with(word: &str, options: &AnagramOptions) -> u128 {
+  try_count_derangements_with(word, options)
+    .expect("derangement count overflowed u128, use `count_derangements_big` instead")
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was, returning `None` if the count does not fit in a `u128`
+pub fn try_count_derangements(word: &str) -> Option<u128> {
+  count_derangements_big(word).to_u128()
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was after normalizing it with the given options, returning `None` on
+/// overflow
+pub fn try_count_derangements_with(word: &str, options: &AnagramOptions) -> Option<u128> {
+  count_derangements_big_with(word, options).to_u128()
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was, exactly
+pub fn count_derangements_big(word: &str) -> BigUint {
+  Letters::new(&AnagramOptions::new().units(word)).count()
+}
+
+/// Count the distinct anagrams of a word in which no letter stays where it
+/// was after normalizing it with the given options, exactly
+pub fn count_derangements_big_with(word: &str, options: &AnagramOptions) -> BigUint {
+  Letters::new(&options.units(&options.normalize(word))).count()
+}
+
+/// Iterate over every distinct anagram of a word in which no letter stays
+/// where it was, in lexicographic order
+///
+/// Yields nothing if the word has no such anagram, which happens exactly when
+/// one letter makes up more than half of it
+/// Examples:
+/// "abc" -> "bca", "cab"
+/// "aaab" -> nothing
+pub fn derangements(word: &str) -> Derangements {
+  Derangements::new(Letters::new(&AnagramOptions::new().units(word)))
+}
+
+/// Iterate over every distinct anagram of a word in which no unit stays where
+/// it was after normalizing it with the given options, in lexicographic order
+pub fn derangements_with(word: &str, options: &AnagramOptions) -> Derangements {
+  Derangements::new(Letters::new(&options.units(&options.normalize(word))))
+}
+
+/// Pick one of the distinct anagrams of a word in which no letter stays where
+/// it was uniformly at random, or `None` if there are none
+pub fn random_derangement<R: Rng + ?Sized>(word: &str, rng: &mut R) -> Option<String> {
+  Letters::new(&AnagramOptions::new().units(word)).sample(rng)
+}
+
+/// Pick one of the distinct anagrams of a word in which no unit stays where
+/// it was after normalizing it with the given options uniformly at random, or
+/// `None` if there are none
+pub fn random_derangement_with<R: Rng + ?Sized>(
+  word: &str,
+  options: &AnagramOptions,
+  rng: &mut R,
+) -> Option<String> {
+  Letters::new(&options.units(&options.normalize(word))).sample(rng)
+}
+
+/// The distinct units of a word, which unit each position starts with, and
+/// how many positions and units of each kind are left to fill
+#[derive(Debug, Clone)]
+struct Letters {
+  units:     Vec<String>,
+  original:  Vec<usize>,
+  positions: Vec<usize>,
+  available: Vec<usize>,
+  left:      usize,
+}
+
+impl Letters {
+  fn new(units: &[&str]) -> Self {
+    let (units, original) = distinct(units);
+
+    let mut available = vec![0; units.len()];
+    for &unit in &original {
+      available[unit] += 1;
+    }
+
+    Self {
+      positions: available.clone(),
+      left: original.len(),
+      units,
+      available,
+      original,
+    }
+  }
+
+  /// Check if the positions left can be filled with the units left so that
+  /// none gets its original unit
+  ///
+  /// Positions that started with one unit can take any other unit, so by
+  /// Hall's theorem this only fails when the positions that can't take a unit
+  /// and the copies of that unit together outnumber the positions left
+  fn feasible(&self) -> bool {
+    self
+      .positions
+      .iter()
+      .zip(&self.available)
+      .all(|(&positions, &available)| positions + available <= self.left)
+  }
+
+  /// Count the ways to fill the positions left, by inclusion-exclusion over
+  /// the positions that get their original unit back
+  ///
+  /// Fixing `j` of the positions that started with a unit to that unit
+  /// leaves the rest free to arrange, and those terms are merged one unit at
+  /// a time, tracking how many units are left to arrange, so every
+  /// intermediate value is an integer
+  fn count(&self) -> BigUint {
+    // ways[m] is the signed sum over the units seen so far of the
+    // arrangements of the m units not fixed in place
+    let mut ways = vec![BigInt::zero(); self.left + 1];
+    ways[0] = 1.into();
+
+    for (&positions, &available) in self.positions.iter().zip(&self.available) {
+      let mut next = vec![BigInt::zero(); self.left + 1];
+
+      for (m, ways) in ways.iter().enumerate().filter(|(_, ways)| !ways.is_zero()) {
+        for fixed in 0..=positions.min(available) {
+          let free = available - fixed;
+          let term = ways
+            * BigInt::from(multinomial_big(&[fixed, positions - fixed]))
+            * BigInt::from(multinomial_big(&[m, free]));
+
+          if fixed % 2 == 0 {
+            next[m + free] += term;
+          } else {
+            next[m + free] -= term;
+          }
+        }
+      }
+
+      ways = next;
+    }
+
+    ways
+      .into_iter()
+      .sum::<BigInt>()
+      .to_biguint()
+      .unwrap_or_default()
+  }
+
+  /// Draw a derangement uniformly by filling positions in order, choosing
+  /// each unit with probability proportional to the ways to finish
+  fn sample<R: Rng + ?Sized>(mut self, rng: &mut R) -> Option<String> {
+    if !self.feasible() {
+      return None;
+    }
+
+    let mut word = String::new();
+
+    while self.left > 0 {
+      let mut choices = Vec::new();
+
+      for unit in 0..self.units.len() {
+        if self.place(unit) {
+          choices.push((unit, self.count()));
+          self.unplace(unit);
+        }
+      }
+
+      let total: BigUint = choices.iter().map(|(_, count)| count).sum();
+      let mut pick = rng.gen_biguint_below(&total);
+
+      for (unit, count) in choices {
+        if pick < count {
+          self.place(unit);
+          word.push_str(&self.units[unit]);
+          break;
+        }
+        pick -= count;
+      }
+    }
+
+    Some(word)
+  }
+}
+
+impl Place for Letters {
+  fn left(&self) -> usize {
+    self.left
+  }
+
+  /// Put a unit in the next position, keeping it only if it isn't the
+  /// position's original unit and the rest can still be filled
+  fn place(&mut self, unit: usize) -> bool {
+    let position = self.original[self.original.len() - self.left];
+
+    if unit == position || self.available[unit] == 0 {
+      return false;
+    }
+
+    self.available[unit] -= 1;
+    self.positions[position] -= 1;
+    self.left -= 1;
+
+    if self.feasible() {
+      true
+    } else {
+      self.unplace(unit);
+      false
+    }
+  }
+
+  fn unplace(&mut self, unit: usize) {
+    self.left += 1;
+    self.available[unit] += 1;
+    self.positions[self.original[self.original.len() - self.left]] += 1;
+  }
+}
+
+/// A lazy iterator over the distinct derangements of a word, created by
+/// [`derangements`]
+#[derive(Debug, Clone)]
+pub struct Derangements {
+  arrangements: Arrangements<Backtrack<Letters>>,
+}
+
+impl Derangements {
+  fn new(letters: Letters) -> Self {
+    let units = letters.units.clone();
+
+    let step = if letters.feasible() {
+      Some(Backtrack::new(units.len(), letters))
+    } else {
+      None
+    };
+
+    Self {
+      arrangements: Arrangements::new(units, step),
+    }
+  }
+
+  /// Write the next derangement into `buffer`, reusing its allocation, and
+  /// return `false` once every derangement has been produced
+  pub fn next_into(&mut self, buffer: &mut String) -> bool {
+    self.arrangements.next_into(buffer)
+  }
+}
+
+impl Iterator for Derangements {
+  type Item = String;
+
+  fn next(&mut self) -> Option<String> {
+    self.arrangements.next_string()
+  }
+}
+
+impl FusedIterator for Derangements {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::anagrams;
+  use rand::{rngs::StdRng, SeedableRng};
+  use std::collections::HashMap;
+
+  /// Count derangements by checking every anagram
+  fn brute_force(word: &str) -> usize {
+    anagrams(word)
+      .filter(|anagram| anagram.chars().zip(word.chars()).all(|(a, b)| a != b))
+      .count()
+  }
+
+  #[test]
+  fn test_count_derangements() {
+    assert_eq!(count_derangements("abc"), 2);
+    assert_eq!(count_derangements("abcd"), 9);
+    assert_eq!(count_derangements("aabb"), 1);
+    assert_eq!(count_derangements("aaab"), 0);
+    assert_eq!(count_derangements("a"), 0);
+    assert_eq!(count_derangements(""), 1);
+
+    for word in &["mississippi", "banana", "abcabc", "aabbbcc", "letters"] {
+      assert_eq!(count_derangements(word), brute_force(word) as u128);
+    }
+
+    assert_eq!(
+      count_derangements_with("A a, B b", &AnagramOptions::phrase()),
+      1
+    );
+  }
+
+  #[test]
+  fn test_count_derangements_big() {
+    let word = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJ";
+    assert_eq!(try_count_derangements(word), None);
+    assert!(count_derangements_big(word) < crate::count_big(word));
+    assert_eq!(try_count_derangements("abcde"), Some(44));
+  }
+
+  #[test]
+  fn test_derangements() {
+    assert_eq!(derangements("abc").collect::<Vec<_>>(), ["bca", "cab"]);
+    assert_eq!(derangements("cab").collect::<Vec<_>>(), ["abc", "bca"]);
+    assert_eq!(derangements("aabb").collect::<Vec<_>>(), ["bbaa"]);
+    assert_eq!(derangements("aaab").count(), 0);
+    assert_eq!(derangements("").collect::<Vec<_>>(), [""]);
+
+    for word in &["mississippi", "banana", "aabbbcc"] {
+      let all: Vec<_> = derangements(word).collect();
+      assert_eq!(all.len(), brute_force(word));
+      assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    assert_eq!(
+      derangements_with("e\u{301}a", &AnagramOptions::new().graphemes(true)).collect::<Vec<_>>(),
+      ["ae\u{301}"]
+    );
+  }
+
+  #[test]
+  fn test_random_derangement() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    assert_eq!(random_derangement("aaab", &mut rng), None);
+    assert_eq!(random_derangement("aabb", &mut rng), Some("bbaa".into()));
+    assert_eq!(
+      random_derangement_with("A, b", &AnagramOptions::phrase(), &mut rng),
+      Some("ba".into())
+    );
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for _ in 0..4000 {
+      let derangement = random_derangement("abcd", &mut rng).unwrap();
+      *seen.entry(derangement).or_insert(0) += 1;
+    }
+
+    assert_eq!(seen.len(), 9);
+    assert!(seen
+      .keys()
+      .all(|word| derangements("abcd").any(|d| &d == word)));
+    assert!(seen.values().all(|&n| (350..550).contains(&n)));
+  }
+}
@@ -0,0 +1,54 @@
+use num_bigint::BigUint;
+use std::fmt::{self, Display, Formatter};
+
+/// An error returned by the functions in [`checked`](crate::checked) where
+/// their unchecked counterparts would panic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+  /// The result does not fit in a `u128`, the `_big` variant of the function
+  /// computes it exactly
+  Overflow,
+  /// A rank is not less than the number of anagrams it indexes into
+  RankOutOfRange {
+    /// The requested rank
+    rank:  BigUint,
+    /// The number of anagrams of the letters
+    count: BigUint,
+  },
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::Overflow => write!(f, "result overflowed u128, use the `_big` variant instead"),
+      Error::RankOutOfRange { rank, count } => write!(
+        f,
+        "anagram rank out of range, {} is not less than the {} anagrams of the letters",
+        rank, count
+      ),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    assert_eq!(
+      Error::Overflow.to_string(),
+      "result overflowed u128, use the `_big` variant instead"
+    );
+    assert_eq!(
+      Error::RankOutOfRange {
+        rank:  BigUint::from(6u32),
+        count: BigUint::from(6u32),
+      }
+      .to_string(),
+      "anagram rank out of range, 6 is not less than the 6 anagrams of the letters"
+    );
+  }
+}
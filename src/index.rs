@@ -0,0 +1,520 @@
+use crate::{AnagramOptions, Cluster, Wildcard, WildcardMatch};
+use std::{
+  cmp::Reverse,
+  collections::{BTreeMap, HashMap},
+  fs::File,
+  io::{self, BufRead, BufReader},
+  iter::FromIterator,
+  path::Path,
+};
+
+/// A dictionary of words grouped by letter signature, so every word sharing
+/// a multiset of letters with a query is found with a single lookup
+///
+/// Words are normalized with the index's options before their signature is
+/// taken, but are stored and returned exactly as they were inserted
+/// Examples:
+/// ["listen", "silent", "enlist", "google"].anagrams_of("tinsel")
+/// -> ["listen", "silent", "enlist"]
+#[derive(Debug, Clone)]
+pub struct AnagramIndex {
+  options:    AnagramOptions,
+  classes:    Vec<Vec<String>>,
+  signatures: HashMap<Vec<String>, usize>,
+  trie:       Vec<Node>,
+  len:        usize,
+}
+
+/// A node in the trie of signatures, where each edge is one unit and units
+/// along a path are in sorted order
+#[derive(Debug, Clone, Default)]
+struct Node {
+  children: BTreeMap<String, usize>,
+  class:    Option<usize>,
+}
+
+impl Default for AnagramIndex {
+  fn default() -> Self {
+    Self {
+      options:    AnagramOptions::default(),
+      classes:    Vec::new(),
+      signatures: HashMap::new(),
+      trie:       vec![Node::default()],
+      len:        0,
+    }
+  }
+}
+
+impl AnagramIndex {
+  /// Create an empty index that compares words exactly as given
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Create an empty index that normalizes words with the given options
+  pub fn with_options(options: AnagramOptions) -> Self {
+    Self {
+      options,
+      ..Self::default()
+    }
+  }
+
+  /// Create an index from a newline delimited word list file, normalizing
+  /// words with the given options
+  pub fn from_file<P: AsRef<Path>>(path: P, options: AnagramOptions) -> io::Result<Self> {
+    let mut index = Self::with_options(options);
+    index.read_from(BufReader::new(File::open(path)?))?;
+    Ok(index)
+  }
+
+  /// Insert every line of a newline delimited word list, skipping blank
+  /// lines and surrounding whitespace
+  pub fn read_from<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+    for line in reader.lines() {
+      self.insert(line?.trim());
+    }
+    Ok(())
+  }
+
+  /// Get the signature of a word, its normalized units in sorted order
+  ///
+  /// Two words are anagrams of each other exactly when their signatures are
+  /// equal, except in grapheme mode, where different units can join into the
+  /// same string
+  pub fn signature(&self, word: &str) -> String {
+    self.sorted_units(word).concat()
+  }
+
+  /// Get the normalized units of a word in sorted order
+  fn sorted_units(&self, word: &str) -> Vec<String> {
+    let normalized = self.options.normalize(word);
+    let mut units: Vec<String> = self
+      .options
+      .units(&normalized)
+      .into_iter()
+      .map(String::from)
+      .collect();
+    units.sort_unstable();
+    units
+  }
+
+  /// Count how many times each normalized unit of a word occurs
+  pub(crate) fn unit_counts(&self, word: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for unit in self.sorted_units(word) {
+      *counts.entry(unit).or_insert(0) += 1;
+    }
+    counts
+  }
+
+  /// Find the class of a signature, creating it and its path through the
+  /// trie if needed
+  fn class_of(&mut self, units: Vec<String>) -> usize {
+    // keyed on the units themselves, since in grapheme mode different units
+    // can concatenate to the same string
+    if let Some(&class) = self.signatures.get(&units) {
+      return class;
+    }
+
+    let mut node = 0;
+    for unit in units.iter().cloned() {
+      node = match self.trie[node].children.get(&unit) {
+        Some(&child) => child,
+        None => {
+          let child = self.trie.len();
+          self.trie.push(Node::default());
+          self.trie[node].children.insert(unit, child);
+          child
+        },
+      };
+    }
+
+    let class = self.classes.len();
+    self.classes.push(Vec::new());
+    self.signatures.insert(units, class);
+    self.trie[node].class = Some(class);
+
+    class
+  }
+
+  /// Insert a word, returning `false` if it was already present or has no
+  /// letters left after normalization
+  pub fn insert(&mut self, word: &str) -> bool {
+    let units = self.sorted_units(word);
+
+    if units.is_empty() {
+      return false;
+    }
+
+    let class = self.class_of(units);
+    let class = &mut self.classes[class];
+
+    if class.iter().any(|existing| existing == word) {
+      return false;
+    }
+
+    class.push(word.to_owned());
+    self.len += 1;
+
+    true
+  }
+
+  /// Get every word in the index that is an anagram of `word`, including
+  /// `word` itself if it was inserted, in insertion order
+  pub fn anagrams_of(&self, word: &str) -> &[String] {
+    self
+      .signatures
+      .get(&self.sorted_units(word))
+      .map_or(&[], |&class| self.classes[class].as_slice())
+  }
+
+  /// Check if the index contains a word
+  pub fn contains(&self, word: &str) -> bool {
+    self
+      .anagrams_of(word)
+      .iter()
+      .any(|existing| existing == word)
+  }
+
+  /// Get every word in the index that can be spelled with a subset of the
+  /// letters of `rack`, using each letter at most as many times as it occurs
+  ///
+  /// Words are found by walking the trie of signatures, only following units
+  /// the rack still has, so words that need a missing letter are never
+  /// visited. Words come back ordered by signature
+  pub fn sub_anagrams(&self, rack: &str) -> Vec<&str> {
+    self
+      .sub_anagram_classes(&mut self.unit_counts(rack))
+      .into_iter()
+      .flatten()
+      .map(String::as_str)
+      .collect()
+  }
+
+  /// Get every class whose signature fits within the given unit counts,
+  /// ordered by signature
+  pub(crate) fn sub_anagram_classes(&self, counts: &mut HashMap<String, usize>) -> Vec<&[String]> {
+    let mut classes = Vec::new();
+    self.collect_sub_anagrams(0, counts, &mut classes);
+    classes
+  }
+
+  fn collect_sub_anagrams<'a>(
+    &'a self,
+    node: usize,
+    counts: &mut HashMap<String, usize>,
+    classes: &mut Vec<&'a [String]>,
+  ) {
+    if let Some(class) = self.trie[node].class {
+      classes.push(&self.classes[class]);
+    }
+
+    for (unit, &child) in &self.trie[node].children {
+      match counts.get_mut(unit) {
+        Some(count) if *count > 0 => *count -= 1,
+        _ => continue,
+      }
+
+      self.collect_sub_anagrams(child, counts, classes);
+
+      *counts.get_mut(unit).unwrap() += 1;
+    }
+  }
+
+  /// Get every word in the index that is an anagram of a pattern whose
+  /// wildcards may each stand in for any letter of the wildcard's alphabet,
+  /// along with the letters the wildcards were assigned
+  ///
+  /// Words come back ordered by signature
+  pub fn anagrams_of_wildcard(&self, pattern: &str, wildcard: &Wildcard) -> Vec<WildcardMatch<'_>> {
+    self.wildcard_matches(pattern, wildcard, true)
+  }
+
+  /// Get every word in the index that can be spelled with a subset of the
+  /// tiles of `rack`, whose wildcards may each stand in for any letter of the
+  /// wildcard's alphabet, along with the letters the wildcards were assigned
+  ///
+  /// Words come back ordered by signature
+  pub fn sub_anagrams_wildcard(&self, rack: &str, wildcard: &Wildcard) -> Vec<WildcardMatch<'_>> {
+    self.wildcard_matches(rack, wildcard, false)
+  }
+
+  fn wildcard_matches(
+    &self,
+    rack: &str,
+    wildcard: &Wildcard,
+    exact: bool,
+  ) -> Vec<WildcardMatch<'_>> {
+    let (blanks, rest) = wildcard.split(rack);
+
+    let mut search = WildcardSearch {
+      counts: self.unit_counts(&rest),
+      tiles: 0,
+      blanks,
+      assignments: Vec::new(),
+      matches: Vec::new(),
+      wildcard,
+      exact,
+    };
+    search.tiles = search.counts.values().sum();
+
+    self.collect_wildcard_matches(0, &mut search);
+    search.matches
+  }
+
+  fn collect_wildcard_matches<'a>(&'a self, node: usize, search: &mut WildcardSearch<'_, 'a>) {
+    if let Some(class) = self.trie[node].class {
+      if !search.exact || (search.tiles == 0 && search.blanks == 0) {
+        for word in &self.classes[class] {
+          search.matches.push(WildcardMatch {
+            word:        word.as_str(),
+            assignments: search.assignments.clone(),
+          });
+        }
+      }
+    }
+
+    for (unit, &child) in &self.trie[node].children {
+      // a real tile is never worse than a blank, which could still be used
+      // for something else
+      match search.counts.get_mut(unit) {
+        Some(count) if *count > 0 => {
+          *count -= 1;
+          search.tiles -= 1;
+          self.collect_wildcard_matches(child, search);
+          *search.counts.get_mut(unit).unwrap() += 1;
+          search.tiles += 1;
+          continue;
+        },
+        _ => {},
+      }
+
+      let letter = match search.wildcard.matches(unit) {
+        Some(letter) if search.blanks > 0 => letter,
+        _ => continue,
+      };
+
+      search.blanks -= 1;
+      search.assignments.push(letter);
+      self.collect_wildcard_matches(child, search);
+      search.assignments.pop();
+      search.blanks += 1;
+    }
+  }
+
+  /// Iterate over every group of words sharing a signature, in the order the
+  /// first word of each group was inserted
+  pub fn classes(&self) -> impl Iterator<Item = &[String]> {
+    self.classes.iter().map(Vec::as_slice)
+  }
+
+  /// Get every group of two or more words sharing a signature, largest
+  /// first, with groups of the same size in the order their first word was
+  /// inserted
+  pub fn clusters(&self) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = self
+      .classes
+      .iter()
+      .filter(|class| class.len() >= 2)
+      .map(|class| Cluster {
+        signature: self.signature(&class[0]),
+        words:     class.clone(),
+      })
+      .collect();
+
+    clusters.sort_by_key(|cluster| Reverse(cluster.words.len()));
+    clusters
+  }
+
+  /// The options words are normalized with
+  pub fn options(&self) -> &AnagramOptions {
+    &self.options
+  }
+
+  /// The number of words in the index
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Check if the index has no words
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+/// The state of a walk through the trie for a rack with wildcards
+struct WildcardSearch<'w, 'a> {
+  wildcard:    &'w Wildcard,
+  exact:       bool,
+  counts:      HashMap<String, usize>,
+  tiles:       usize,
+  blanks:      usize,
+  assignments: Vec<char>,
+  matches:     Vec<WildcardMatch<'a>>,
+}
+
+impl<S: AsRef<str>> Extend<S> for AnagramIndex {
+  fn extend<I: IntoIterator<Item = S>>(&mut self, words: I) {
+    for word in words {
+      self.insert(word.as_ref());
+    }
+  }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for AnagramIndex {
+  fn from_iter<I: IntoIterator<Item = S>>(words: I) -> Self {
+    let mut index = Self::new();
+    index.extend(words);
+    index
+  }
+}
+
+/// Get every word in a dictionary that can be spelled with a subset of the
+/// letters of `rack`, respecting how many times each letter occurs
+///
+/// Examples:
+/// ("tesla", ["east", "seat", "steal", "least", "sell"])
+/// -> ["steal", "least", "east", "seat"]
+pub fn sub_anagrams<'a>(rack: &str, dict: &'a AnagramIndex) -> Vec<&'a str> {
+  dict.sub_anagrams(rack)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{env, fs};
+
+  #[test]
+  fn test_anagrams_of() {
+    let index: AnagramIndex = ["listen", "silent", "enlist", "google", "silent", ""]
+      .iter()
+      .collect();
+
+    assert_eq!(index.len(), 4);
+    assert_eq!(index.anagrams_of("tinsel"), ["listen", "silent", "enlist"]);
+    assert_eq!(index.anagrams_of("google"), ["google"]);
+    assert!(index.anagrams_of("rust").is_empty());
+    assert!(index.anagrams_of("Tinsel").is_empty());
+    assert!(index.contains("enlist"));
+    assert!(!index.contains("tinsel"));
+  }
+
+  #[test]
+  fn test_anagrams_of_with_options() {
+    let mut index = AnagramIndex::with_options(AnagramOptions::phrase());
+    index.extend(&["Dormitory", "dirty room", "Elvis", "lives", "Élvis"]);
+
+    assert_eq!(index.anagrams_of("ROOM, DIRTY!"), [
+      "Dormitory",
+      "dirty room"
+    ]);
+    assert_eq!(index.anagrams_of("evils"), ["Elvis", "lives"]);
+    assert_eq!(index.signature("Lives!"), "eilsv");
+    assert_eq!(index.classes().count(), 3);
+
+    // a lone accent and an accented letter sort to the same string
+    let mut index = AnagramIndex::with_options(AnagramOptions::new().graphemes(true));
+    index.extend(&["\u{301}a", "a\u{301}"]);
+    assert_eq!(index.anagrams_of("a\u{301}"), ["a\u{301}"]);
+    assert_eq!(index.classes().count(), 2);
+  }
+
+  #[test]
+  fn test_sub_anagrams() {
+    let index: AnagramIndex = ["east", "seat", "steal", "least", "sell", "tea", "a", "tt"]
+      .iter()
+      .collect();
+
+    let mut words = sub_anagrams("tesla", &index);
+    words.sort_unstable();
+    assert_eq!(words, ["a", "east", "least", "seat", "steal", "tea"]);
+
+    assert_eq!(sub_anagrams("tesla", &index), index.sub_anagrams("tesla"));
+    assert_eq!(sub_anagrams("tt", &index), ["tt"]);
+    assert!(sub_anagrams("t", &index).is_empty());
+    assert!(sub_anagrams("", &index).is_empty());
+  }
+
+  #[test]
+  fn test_sub_anagrams_with_options() {
+    let mut index = AnagramIndex::with_options(AnagramOptions::phrase().strip_diacritics(true));
+    index.extend(&["Café", "face", "ace", "décaf", "fade"]);
+
+    let mut words = index.sub_anagrams("F, A, C, E!");
+    words.sort_unstable();
+    assert_eq!(words, ["Café", "ace", "face"]);
+  }
+
+  #[test]
+  fn test_anagrams_of_wildcard() {
+    let index: AnagramIndex = ["rent", "tern", "tree", "rest", "ret", "tart"]
+      .iter()
+      .collect();
+    let wildcard = Wildcard::new('?');
+
+    let matches: Vec<_> = index
+      .anagrams_of_wildcard("re?t", &wildcard)
+      .into_iter()
+      .map(|found| (found.word, found.assignments))
+      .collect();
+    assert_eq!(matches, [
+      ("tree", vec!['e']),
+      ("rent", vec!['n']),
+      ("tern", vec!['n']),
+      ("rest", vec!['s']),
+    ]);
+
+    assert_eq!(index.anagrams_of_wildcard("??rt", &wildcard).len(), 5);
+    assert!(index.anagrams_of_wildcard("x?", &wildcard).is_empty());
+    assert!(index
+      .anagrams_of_wildcard("re?t", &Wildcard::new('?').alphabet("xyz".chars()))
+      .is_empty());
+  }
+
+  #[test]
+  fn test_sub_anagrams_wildcard() {
+    let index: AnagramIndex = ["east", "seat", "tea", "zest", "tt"].iter().collect();
+
+    let mut matches: Vec<_> = index
+      .sub_anagrams_wildcard("tea?", &Wildcard::new('?'))
+      .into_iter()
+      .map(|found| (found.word, found.assignments))
+      .collect();
+    matches.sort_unstable();
+    assert_eq!(matches, [
+      ("east", vec!['s']),
+      ("seat", vec!['s']),
+      ("tea", vec![]),
+      ("tt", vec!['t']),
+    ]);
+
+    assert!(index
+      .sub_anagrams_wildcard("", &Wildcard::new('?'))
+      .is_empty());
+  }
+
+  #[test]
+  fn test_read_from() {
+    let mut index = AnagramIndex::new();
+    index
+      .read_from(&b"stop\n  pots \n\ntops\r\nspot\nopts"[..])
+      .unwrap();
+
+    assert_eq!(index.anagrams_of("post"), [
+      "stop", "pots", "tops", "spot", "opts"
+    ]);
+  }
+
+  #[test]
+  fn test_from_file() {
+    let path = env::temp_dir().join(format!("anagram-index-{}.txt", std::process::id()));
+    fs::write(&path, "Stressed\ndesserts\nstar\nrats\n").unwrap();
+
+    let index = AnagramIndex::from_file(&path, AnagramOptions::phrase()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(index.anagrams_of("DESSERTS"), ["Stressed", "desserts"]);
+    assert_eq!(index.anagrams_of("tsar"), ["star", "rats"]);
+    assert!(AnagramIndex::from_file(&path, AnagramOptions::new()).is_err());
+  }
+}
@@ -5,7 +5,7 @@
 //!
 //! ## Examples
 //! ```
-//! use anagram::{count, get_next, is_anagram, occurences};
+//! use anagram::{anagrams, count, get_next, is_anagram, occurences};
 //!
 //! fn main() {
 //!   // count how many anagrams can be formed from a given word
@@ -25,21 +25,81 @@
 //!   assert_eq!(next, "abcdegf");
 //!
 //!   // get all anagrams of a word
-//!   let mut word: String = String::from("abc");
-//!   for _ in 0..count(&word) {
-//!     // get next anagram
-//!     word = get_next(&word);
-//!     println!("{}", word);
+//!   for anagram in anagrams("abc") {
+//!     println!("{}", anagram);
 //!   }
 //! }
 //! ```
-use counter::Counter;
-use std::{collections::HashSet, str::from_utf8};
+pub use crate::{
+  anagrams::{anagrams, anagrams_with, Anagrams},
+  cluster::{clusters, clusters_with, write_clusters, Cluster, ClusterFormat},
+  derangement::{
+    count_derangements, count_derangements_big, count_derangements_big_with,
+    count_derangements_with, derangements, derangements_with, random_derangement,
+    random_derangement_with, try_count_derangements, try_count_derangements_with, Derangements,
+  },
+  error::Error,
+  index::{sub_anagrams, AnagramIndex},
+  multi::{MultiAnagramSearcher, MultiMatch, MultiMatches},
+  options::{AnagramOptions, Case, Digits, Normalization},
+  partial::{
+    count_k, count_k_big, count_k_big_with, count_k_with, partial_anagrams, partial_anagrams_with,
+    try_count_k, try_count_k_with, PartialAnagrams,
+  },
+  permutation::{next_permutation, prev_permutation},
+  phrase::{phrase_anagrams, PhraseAnagrams, PhraseSolver},
+  random::{random_anagram, random_anagram_with, Sampler},
+  rank::{
+    rank, rank_big, rank_big_with, rank_with, try_rank, try_rank_with, unrank, unrank_big,
+    unrank_big_with, unrank_with,
+  },
+  search::{
+    find_anagrams, find_anagrams_with, find_approximate_anagrams, find_approximate_anagrams_with,
+    FindAnagrams, Match,
+  },
+  stream::{
+    find_anagrams_in_reader, find_anagrams_in_reader_with, occurences_in_reader,
+    occurences_in_reader_with, ReaderMatches, StreamMatch,
+  },
+  wildcard::{
+    count_wildcard, count_wildcard_big, count_wildcard_big_with, count_wildcard_with,
+    is_anagram_wildcard, is_anagram_wildcard_with, try_count_wildcard, try_count_wildcard_with,
+    Wildcard, WildcardMatch,
+  },
+  words::{
+    count_word_orderings, count_word_orderings_big, count_word_orderings_big_with,
+    count_word_orderings_with, is_word_anagram, is_word_anagram_with, next_word_ordering,
+    next_word_ordering_with, prev_word_ordering, prev_word_ordering_with, try_count_word_orderings,
+    try_count_word_orderings_with, word_orderings, word_orderings_rev, word_orderings_rev_with,
+    word_orderings_with, Tokenizer, WordOrderings,
+  },
+};
 
-static ASCII_LOWER: [char; 26] = [
-  'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
-  't', 'u', 'v', 'w', 'x', 'y', 'z',
-];
+use counter::Counter;
+use num_bigint::BigUint;
+use num_traits::One;
+use std::{collections::HashMap, hash::Hash};
+
+mod anagrams;
+mod arrangement;
+pub mod checked;
+mod cluster;
+mod derangement;
+mod error;
+mod index;
+mod multi;
+mod options;
+mod partial;
+mod permutation;
+mod phrase;
+mod random;
+mod rank;
+mod search;
+mod stream;
+mod wildcard;
+mod words;
+
+use crate::search::Matches;
 
 fn factorial(n: u128) -> u128 {
   if n <= 1 {
@@ -49,114 +109,231 @@ fn factorial(n: u128) -> u128 {
   }
 }
 
-/// Count the number of anagrams that can be formed from a word
-pub fn count(word: &str) -> u128 {
-  let mut unique = HashSet::new();
-  let count: usize = word.chars().count();
+/// The largest `n` for which `n!` fits in a `u128`
+const MAX_FACTORIAL: usize = 34;
+
+/// Get the number of times each distinct unit occurs in a word
+fn multiplicities<T: Hash + Eq>(units: impl IntoIterator<Item = T>) -> Vec<usize> {
+  units
+    .into_iter()
+    .collect::<Counter<_>>()
+    .values()
+    .copied()
+    .collect()
+}
 
-  for c in word.chars() {
-    unique.insert(c);
+/// Compute the multinomial coefficient `(k1 + k2 + ...)! / (k1! k2! ...)`
+/// one binomial factor at a time, so that intermediate values never exceed
+/// the final result
+fn multinomial(counts: &[usize]) -> Option<u128> {
+  let mut result: u128 = 1;
+  let mut total: u128 = 0;
+
+  for &k in counts {
+    for i in 1..=k as u128 {
+      total += 1;
+      // divide out the common factor first so the multiplication only
+      // overflows when the result itself does
+      let g = gcd(result, i);
+      result = (result / g).checked_mul(total / (i / g))?;
+    }
   }
 
-  if unique.len() == count {
-    factorial(count as u128)
-  } else {
-    let mut seen = HashSet::new();
-
-    let mut divisor: u128 = 1;
+  Some(result)
+}
 
-    let char_counts = word.chars().collect::<Counter<_>>();
+/// Compute the multinomial coefficient exactly
+fn multinomial_big(counts: &[usize]) -> BigUint {
+  let mut result = BigUint::one();
+  let mut total: u64 = 0;
 
-    for c in word.chars() {
-      if !seen.contains(&c) {
-        divisor *= char_counts[&c] as u128;
-        seen.insert(c);
-      }
+  for &k in counts {
+    for i in 1..=k as u64 {
+      total += 1;
+      result = result * total / i;
     }
+  }
 
-    factorial(count as u128) / divisor
+  result
+}
+
+/// Count the arrangements of units with the given multiplicities, returning
+/// `None` on overflow
+fn try_count_multiplicities(counts: &[usize]) -> Option<u128> {
+  if counts.iter().all(|&k| k == 1) && counts.len() <= MAX_FACTORIAL {
+    Some(factorial(counts.len() as u128))
+  } else {
+    multinomial(counts)
   }
 }
 
-/// Count the number of occurences of an anagram in a word
-pub fn occurences(word: &str, input: &str) -> u128 {
-  let len_word = word.chars().count();
-  let len_input = input.chars().count();
-
-  // Check if all counts are zero
-  let is_zero = |count: &[i64]| {
-    for val in count.iter() {
-      if *val != 0 {
-        return false;
-      }
-    }
-    true
-  };
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+  while b != 0 {
+    let t = a % b;
+    a = b;
+    b = t;
+  }
+  a
+}
 
-  let mut count: [i64; 256 as usize] = [0; 256 as usize];
+/// Count the number of anagrams that can be formed from a word
+///
+/// Panics if the count does not fit in a `u128`, see `try_count` and
+/// `count_big` for long words
+pub fn count(word: &str) -> u128 {
+  try_count(word).expect("anagram count overflowed u128, use `count_big` instead")
+}
 
-  for val in 0..len_input {
-    count[word.as_bytes()[val] as usize] += 1;
-  }
+/// Count the number of anagrams that can be formed from a word after
+/// normalizing it with the given options
+pub fn count_with(word: &str, options: &AnagramOptions) -> u128 {
+  try_count_with(word, options).expect("anagram count overflowed u128, use `count_big` instead")
+}
 
-  for val in 0..len_input {
-    count[input.as_bytes()[val] as usize] -= 1;
-  }
+/// Count the number of anagrams that can be formed from a word, returning
+/// `None` if the count does not fit in a `u128`
+pub fn try_count(word: &str) -> Option<u128> {
+  try_count_arrangements(word.chars())
+}
+
+/// Count the number of anagrams that can be formed from a word after
+/// normalizing it with the given options, returning `None` on overflow
+pub fn try_count_with(word: &str, options: &AnagramOptions) -> Option<u128> {
+  let normalized = options.normalize(word);
+  try_count_arrangements(options.units(&normalized))
+}
 
-  let mut result: u128 = 0;
-  result += is_zero(&count) as u128;
+/// Count the number of anagrams that can be formed from a word, exactly,
+/// no matter how long the word is
+pub fn count_big(word: &str) -> BigUint {
+  count_arrangements_big(word.chars())
+}
 
-  for i in len_input..len_word {
-    // add last character
-    count[word.as_bytes()[i] as usize] += 1;
+/// Count the number of anagrams that can be formed from a word after
+/// normalizing it with the given options, exactly
+pub fn count_big_with(word: &str, options: &AnagramOptions) -> BigUint {
+  let normalized = options.normalize(word);
+  count_arrangements_big(options.units(&normalized))
+}
 
-    // remove first character
-    count[word.as_bytes()[i - len_input] as usize] -= 1;
+/// Count the distinct orderings of any items, where equal items are
+/// interchangeable
+///
+/// Panics if the count does not fit in a `u128`, see
+/// `try_count_arrangements` and `count_arrangements_big` for long sequences
+/// Examples:
+/// ["to", "be", "or", "not", "to", "be"] -> 180
+/// [1, 1, 2] -> 3
+pub fn count_arrangements<I, T>(items: I) -> u128
+where
+  I: IntoIterator<Item = T>,
+  T: Hash + Eq,
+{
+  try_count_arrangements(items)
+    .expect("arrangement count overflowed u128, use `count_arrangements_big` instead")
+}
 
-    result += is_zero(&count) as u128;
-  }
-  result
+/// Count the distinct orderings of any items, where equal items are
+/// interchangeable, returning `None` if the count does not fit in a `u128`
+pub fn try_count_arrangements<I, T>(items: I) -> Option<u128>
+where
+  I: IntoIterator<Item = T>,
+  T: Hash + Eq,
+{
+  try_count_multiplicities(&multiplicities(items))
+}
+
+/// Count the distinct orderings of any items, where equal items are
+/// interchangeable, exactly
+pub fn count_arrangements_big<I, T>(items: I) -> BigUint
+where
+  I: IntoIterator<Item = T>,
+  T: Hash + Eq,
+{
+  multinomial_big(&multiplicities(items))
 }
 
-/// Check if a word is an anagram of another word
+/// Count the number of occurences of an anagram in a word
+///
+/// Slides a window the length of `input` over the chars of `word`, so any
+/// Unicode text works and an `input` longer than `word` simply never matches
+pub fn occurences(word: &str, input: &str) -> u128 {
+  occurences_with(word, input, &AnagramOptions::new())
+}
+
+/// Count the number of occurences of an anagram in a word after normalizing
+/// both with the given options, sliding over grapheme clusters if the options
+/// ask for them
+///
+/// Each unit is normalized on its own, so ignored chars are skipped over and
+/// a match may span them
+pub fn occurences_with(word: &str, input: &str, options: &AnagramOptions) -> u128 {
+  Matches::new(word, input, 0, options).count() as u128
+}
+
+/// Count the number of occurences of an anagram in a word with up to
+/// `mismatches` letters substituted, so that misspelled or damaged scrambles
+/// are still found
+///
+/// A window matches when the histograms of the window and of `input` differ
+/// by at most `2 * mismatches`
+pub fn approximate_occurences(word: &str, input: &str, mismatches: usize) -> u128 {
+  approximate_occurences_with(word, input, mismatches, &AnagramOptions::new())
+}
+
+/// Count the number of occurences of an anagram in a word with up to
+/// `mismatches` letters substituted, after normalizing both with the given
+/// options
+pub fn approximate_occurences_with(
+  word: &str,
+  input: &str,
+  mismatches: usize,
+  options: &AnagramOptions,
+) -> u128 {
+  Matches::new(word, input, mismatches, options).count() as u128
+}
+
+/// Check if a word is an anagram of another word, ignoring case
 pub fn is_anagram(left: &str, right: &str) -> bool {
-  if left.chars().count() != right.chars().count() {
-    false
-  } else {
-    let mut count: [i32; 26 as usize] = [0; 26 as usize];
-
-    for c in left.chars() {
-      let pos = if let Some(val) = c.to_digit(10) {
-        val as usize
-      } else {
-        ASCII_LOWER
-          .iter()
-          .position(|&x| x == c.to_lowercase().nth(0).unwrap())
-          .unwrap() as usize
-      };
-
-      count[pos] += 1;
-    }
+  is_anagram_with(left, right, &AnagramOptions::new().case(Case::Lower))
+}
 
-    for c in right.chars() {
-      let pos = if let Some(val) = c.to_digit(10) {
-        val as usize
-      } else {
-        ASCII_LOWER
-          .iter()
-          .position(|&x| x == c.to_lowercase().nth(0).unwrap())
-          .unwrap() as usize
-      };
-
-      count[pos] -= 1;
-
-      if count[pos] < 0 {
-        return false;
-      }
-    }
+/// Check if a word is an anagram of another word after normalizing both with
+/// the given options
+///
+/// Works on any Unicode text by comparing char histograms, or histograms of
+/// grapheme clusters if the options ask for them, so digits, punctuation and
+/// non-Latin letters are all counted as themselves unless the options say
+/// otherwise
+pub fn is_anagram_with(left: &str, right: &str, options: &AnagramOptions) -> bool {
+  let (left, right) = (options.normalize(left), options.normalize(right));
+  let mut count: HashMap<&str, i64> = HashMap::new();
+
+  for unit in options.units(&left) {
+    *count.entry(unit).or_insert(0) += 1;
+  }
 
-    true
+  for unit in options.units(&right) {
+    match count.get_mut(unit) {
+      Some(n) if *n > 0 => *n -= 1,
+      _ => return false,
+    }
   }
+
+  count.values().all(|&n| n == 0)
+}
+
+/// Permute the units of a normalized word with `step`, returning the result
+/// and whether `step` reported success
+fn step_with(
+  word: &str,
+  options: &AnagramOptions,
+  step: fn(&mut [&str]) -> bool,
+) -> (String, bool) {
+  let normalized = options.normalize(word);
+  let mut units = options.units(&normalized);
+  let stepped = step(&mut units);
+  (units.concat(), stepped)
 }
 
 /// Get the next lexicographically greater permutation
@@ -167,47 +344,84 @@ pub fn is_anagram(left: &str, right: &str) -> bool {
 /// "abc" -> "acb"
 /// "cba" -> "abc"
 pub fn get_next(word: &str) -> String {
-  let mut i = word.chars().count() - 1;
+  let mut chars: Vec<char> = word.chars().collect();
+  next_permutation(&mut chars);
+  chars.into_iter().collect()
+}
 
-  // find the first char smaller than the char next to it
-  while i > 0 {
-    if word.as_bytes()[i] > word.as_bytes()[i - 1] {
-      break;
-    }
-    i -= 1;
-  }
+/// Get the next lexicographically greater permutation of a word after
+/// normalizing it with the given options, permuting grapheme clusters if the
+/// options ask for them
+pub fn get_next_with(word: &str, options: &AnagramOptions) -> String {
+  step_with(word, options, |units| next_permutation(units)).0
+}
 
-  // we are at the lexicographically greatest permutation so we
-  // return the lexicographically smallest one
-  if i == 0 {
-    let mut chars: Vec<char> = word.chars().collect();
-    chars.sort();
-    return chars.into_iter().collect();
+/// Get the previous lexicographically smaller permutation
+/// This function will return either the previous smaller permutation or the
+/// lexicographically greatest permutation if the given word is the
+/// lexicographically smallest permutation
+/// Examples:
+/// "acb" -> "abc"
+/// "abc" -> "cba"
+pub fn get_prev(word: &str) -> String {
+  let mut chars: Vec<char> = word.chars().collect();
+  prev_permutation(&mut chars);
+  chars.into_iter().collect()
+}
+
+/// Get the previous lexicographically smaller permutation of a word after
+/// normalizing it with the given options, permuting grapheme clusters if the
+/// options ask for them
+pub fn get_prev_with(word: &str, options: &AnagramOptions) -> String {
+  step_with(word, options, |units| prev_permutation(units)).0
+}
+
+/// Get the next lexicographically greater permutation, or `None` if the
+/// given word is already the lexicographically greatest permutation
+/// Examples:
+/// "abc" -> Some("acb")
+/// "cba" -> None
+pub fn try_next(word: &str) -> Option<String> {
+  let mut chars: Vec<char> = word.chars().collect();
+
+  if next_permutation(&mut chars) {
+    Some(chars.into_iter().collect())
+  } else {
+    None
   }
+}
 
-  // find the smallest char on the right side of i-1'th char
-  // that's greater than word[i - 1]
-  let mut j = i + 1;
-  let mut smallest = i;
-  let mut word_as_bytes: Vec<u8> = word.to_string().into_bytes();
-  while j < word.chars().count() {
-    if word_as_bytes[j] > word_as_bytes[i - 1] && word_as_bytes[j] < word_as_bytes[smallest] {
-      smallest = j;
-    }
-    j += 1;
+/// Get the next lexicographically greater permutation of a word after
+/// normalizing it with the given options, without wrapping around
+pub fn try_next_with(word: &str, options: &AnagramOptions) -> Option<String> {
+  match step_with(word, options, |units| next_permutation(units)) {
+    (next, true) => Some(next),
+    (_, false) => None,
   }
+}
 
-  // swap smallest with word[i - 1]
-  word_as_bytes.swap(smallest, i - 1);
+/// Get the previous lexicographically smaller permutation, or `None` if the
+/// given word is already the lexicographically smallest permutation
+/// Examples:
+/// "acb" -> Some("abc")
+/// "abc" -> None
+pub fn try_prev(word: &str) -> Option<String> {
+  let mut chars: Vec<char> = word.chars().collect();
 
-  // sort right half
-  let mut right_half: Vec<u8> = word_as_bytes[i..word.chars().count()].to_vec();
-  right_half.sort();
+  if prev_permutation(&mut chars) {
+    Some(chars.into_iter().collect())
+  } else {
+    None
+  }
+}
 
-  // merge back and return as a String
-  from_utf8(&[&word_as_bytes[0..i], &right_half].concat())
-    .unwrap()
-    .to_string()
+/// Get the previous lexicographically smaller permutation of a word after
+/// normalizing it with the given options, without wrapping around
+pub fn try_prev_with(word: &str, options: &AnagramOptions) -> Option<String> {
+  match step_with(word, options, |units| prev_permutation(units)) {
+    (prev, true) => Some(prev),
+    (_, false) => None,
+  }
 }
 
 #[cfg(test)]
@@ -232,9 +446,57 @@ mod tests {
     );
     assert_eq!(count("abcdefghijklmabcdefghijklm"), 49229914688306352000000);
     assert_eq!(count("abcdABCDabcd"), 29937600);
+    assert_eq!(count(""), 1);
+    assert_eq!(count("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab"), 41);
+  }
+
+  #[test]
+  fn test_try_count() {
+    assert_eq!(try_count("ordeals"), Some(5040));
+    assert_eq!(
+      try_count("abcdefghijklmnopqrstuvwxyzABCDEFGH"),
+      Some(factorial(34))
+    );
+    assert_eq!(try_count("abcdefghijklmnopqrstuvwxyzABCDEFGHI"), None);
+    assert_eq!(
+      try_count("aaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbbbbbb"),
+      Some(137846528820)
+    );
+  }
+
+  #[test]
+  fn test_count_big() {
+    assert_eq!(count_big("ordeals"), BigUint::from(5040u32));
+    assert_eq!(count_big("abcdABCDabcd"), BigUint::from(29937600u32));
+    assert_eq!(
+      count_big("abcdefghijklmnopqrstuvwxyzABCDEFGHI").to_string(),
+      "10333147966386144929666651337523200000000"
+    );
+    assert_eq!(
+      count_big("abcdefghijklmnopqrstuvwxyzABCDEFGHI")
+        / count_big("abcdefghijklmnopqrstuvwxyzABCDEFGH"),
+      BigUint::from(35u32)
+    );
   }
 
   #[test]
+  fn test_count_arrangements() {
+    let sentence = "to be or not to be".split(' ');
+    assert_eq!(count_arrangements(sentence.clone()), 180);
+    assert_eq!(count_arrangements_big(sentence), BigUint::from(180u32));
+    assert_eq!(count_arrangements(vec![1, 1, 2]), 3);
+    assert_eq!(count_arrangements(Vec::<u8>::new()), 1);
+    assert_eq!(count_arrangements(["x"; 100]), 1);
+    assert_eq!(try_count_arrangements(0..34), Some(factorial(34)));
+    assert_eq!(try_count_arrangements(0..35), None);
+    assert_eq!(
+      count_arrangements_big(0..35),
+      count_big("abcdefghijklmnopqrstuvwxyzABCDEFGHI")
+    );
+  }
+
+  #[test]
+  #[allow(clippy::bool_assert_comparison)]
   fn test_is_anagram() {
     assert_eq!(is_anagram("hello", "ooo"), false);
     assert_eq!(is_anagram("Hello", "olleH"), true);
@@ -250,6 +512,24 @@ mod tests {
     assert_eq!(is_anagram("1102eeaA", "0112eaAe"), true);
   }
 
+  #[test]
+  fn test_is_anagram_unicode() {
+    assert!(!is_anagram("1", "b"));
+    assert!(!is_anagram("a1", "ab"));
+    assert!(is_anagram("a b!", "!b a"));
+    assert!(!is_anagram("a b", "ab"));
+    assert!(!is_anagram("ab", "ab "));
+    assert!(is_anagram("été", "tÉé"));
+    assert!(!is_anagram("été", "ete"));
+    assert!(is_anagram("Straße", "ßartse"));
+    assert!(is_anagram("ΑΘΗΝΑ", "ηναθα"));
+    assert!(is_anagram("日本語", "語日本"));
+    assert!(is_anagram("🦀🎉", "🎉🦀"));
+    assert!(!is_anagram("🦀", "🎉"));
+    assert!(is_anagram("", ""));
+    assert!(!is_anagram("", "a"));
+  }
+
   #[test]
   fn test_occurences() {
     assert_eq!(occurences("forxxorfxdofr", "for"), 3);
@@ -260,6 +540,63 @@ mod tests {
     assert_eq!(occurences("anagrams", "smargana"), 1);
   }
 
+  #[test]
+  fn test_occurences_unicode() {
+    assert_eq!(occurences("ñañaña", "añ"), 5);
+    assert_eq!(occurences("日本語日本", "本日"), 2);
+    assert_eq!(occurences("🦀🎉🦀", "🎉🦀"), 2);
+    assert_eq!(occurences("ab", "abc"), 0);
+    assert_eq!(occurences("", "a"), 0);
+    assert_eq!(occurences("abc", ""), 0);
+    assert_eq!(occurences("Ab", "ab"), 0);
+  }
+
+  #[test]
+  fn test_approximate_occurences() {
+    assert_eq!(approximate_occurences("forxxorfxdofr", "for", 0), 3);
+    assert_eq!(approximate_occurences("forxxorfxdofr", "for", 1), 7);
+    assert_eq!(approximate_occurences("forxxorfxdofr", "for", 3), 11);
+    assert_eq!(approximate_occurences("ab", "abc", 1), 0);
+    assert_eq!(approximate_occurences("hel1o", "hello", 1), 1);
+    assert_eq!(
+      approximate_occurences_with("HEL1O world", "hello", 1, &AnagramOptions::phrase()),
+      1
+    );
+  }
+
+  #[test]
+  fn test_occurences_with() {
+    assert_eq!(
+      occurences_with("Ab, BA", "ab", &AnagramOptions::phrase()),
+      2
+    );
+    assert_eq!(
+      occurences_with(
+        "Crème brûlée",
+        "EE",
+        &AnagramOptions::phrase().strip_diacritics(true)
+      ),
+      1
+    );
+    assert_eq!(occurences_with("e\u{301}e", "e", &AnagramOptions::new()), 2);
+    assert_eq!(
+      occurences_with("e\u{301}e", "e", &AnagramOptions::new().graphemes(true)),
+      1
+    );
+    assert_eq!(
+      occurences_with("Straße", "SS", &AnagramOptions::new().case(Case::Fold)),
+      1
+    );
+
+    // marks compose with the char before them just like in is_anagram_with
+    let nfc = AnagramOptions::new().normalization(Normalization::Nfc);
+    for &(word, input) in &[("e\u{301}", "\u{e9}"), ("\u{e9}", "e\u{301}")] {
+      assert!(is_anagram_with(word, input, &nfc));
+      assert_eq!(occurences_with(word, input, &nfc), 1);
+    }
+    assert_eq!(occurences_with("xe\u{301}ye\u{301}", "\u{e9}y", &nfc), 2);
+  }
+
   #[test]
   fn test_get_next() {
     assert_eq!(get_next("abc"), "acb");
@@ -270,5 +607,116 @@ mod tests {
     assert_eq!(get_next("1234"), "1243");
     assert_eq!(get_next("4321"), "1234");
     assert_eq!(get_next("534976"), "536479");
+    assert_eq!(get_next(""), "");
+  }
+
+  #[test]
+  fn test_get_next_unicode() {
+    assert_eq!(get_next("ñaé"), "ñéa");
+    assert_eq!(get_next("éñ"), "ñé");
+    assert_eq!(get_next("ñé"), "éñ");
+    assert_eq!(get_next("日本語"), "日語本");
+    assert_eq!(get_next("a🦀b"), "ba🦀");
+  }
+
+  #[test]
+  fn test_get_next_graphemes() {
+    let options = AnagramOptions::new().graphemes(true);
+    assert_eq!(get_next_with("e\u{301}a", &options), "ae\u{301}");
+    assert_eq!(get_next_with("ae\u{301}", &options), "e\u{301}a");
+    assert_eq!(get_next_with("👍🏽a", &options), "a👍🏽");
+    assert_eq!(get_prev_with("a👍🏽", &options), "👍🏽a");
+    assert_eq!(try_next_with("👨‍👩‍👧b", &options), None);
+    assert_eq!(try_prev_with("👨‍👩‍👧b", &options), Some("b👨‍👩‍👧".into()));
+    assert_eq!(count_with("👨‍👩‍👧👨‍👩‍👧b", &options), 3);
+    assert_eq!(count_big_with("👍🏽👍🏽b", &options), BigUint::from(3u32));
+    assert_eq!(
+      get_next_with("ae\u{301}", &AnagramOptions::new()),
+      "a\u{301}e"
+    );
+  }
+
+  #[test]
+  fn test_get_prev() {
+    assert_eq!(get_prev("acb"), "abc");
+    assert_eq!(get_prev("bca"), "bac");
+    assert_eq!(get_prev("aaa"), "aaa");
+    assert_eq!(get_prev("abc"), "cba");
+    assert_eq!(get_prev("251678"), "218765");
+    assert_eq!(get_prev("1243"), "1234");
+    assert_eq!(get_prev("1234"), "4321");
+    assert_eq!(get_prev("536479"), "534976");
+    assert_eq!(get_prev(&get_next("ordeals")), "ordeals");
+  }
+
+  #[test]
+  fn test_try_next() {
+    assert_eq!(try_next("abc"), Some("acb".into()));
+    assert_eq!(try_next("aab"), Some("aba".into()));
+    assert_eq!(try_next("cba"), None);
+    assert_eq!(try_next("aaa"), None);
+    assert_eq!(try_next(""), None);
+    assert_eq!(try_next("218765"), Some("251678".into()));
+  }
+
+  #[test]
+  fn test_try_prev() {
+    assert_eq!(try_prev("acb"), Some("abc".into()));
+    assert_eq!(try_prev("baa"), Some("aba".into()));
+    assert_eq!(try_prev("abc"), None);
+    assert_eq!(try_prev("aaa"), None);
+    assert_eq!(try_prev(""), None);
+    assert_eq!(try_prev("251678"), Some("218765".into()));
+  }
+
+  #[test]
+  fn test_is_anagram_with() {
+    let phrase = AnagramOptions::phrase();
+    assert!(is_anagram_with("Dormitory", "Dirty room!", &phrase));
+    assert!(is_anagram_with(
+      "Clint Eastwood",
+      "Old West action",
+      &phrase
+    ));
+    assert!(!is_anagram_with(
+      "Dormitory",
+      "Dirty room!",
+      &AnagramOptions::new()
+    ));
+    assert!(!is_anagram_with("Hello", "olleh", &AnagramOptions::new()));
+    assert!(is_anagram_with(
+      "Crème",
+      "merce",
+      &AnagramOptions::new()
+        .strip_diacritics(true)
+        .case(Case::Lower)
+    ));
+    assert!(is_anagram_with(
+      "r2-d2",
+      "dr",
+      &phrase.digits(Digits::Ignore)
+    ));
+
+    let graphemes = AnagramOptions::new().graphemes(true);
+    assert!(!is_anagram_with("e\u{301}a", "a\u{301}e", &graphemes));
+    assert!(is_anagram_with("e\u{301}a", "ae\u{301}", &graphemes));
+    assert!(is_anagram_with(
+      "e\u{301}a",
+      "a\u{301}e",
+      &AnagramOptions::new()
+    ));
+  }
+
+  #[test]
+  fn test_with_options() {
+    let options = AnagramOptions::phrase();
+    assert_eq!(count_with("A b, A!", &options), 3);
+    assert_eq!(try_count_with("A b, A!", &options), Some(3));
+    assert_eq!(count_big_with("A b, A!", &options), BigUint::from(3u32));
+    assert_eq!(occurences_with("Hello World hello", "LL", &options), 2);
+    assert_eq!(get_next_with("A, c b", &options), "bac");
+    assert_eq!(get_prev_with("A, c b", &options), "abc");
+    assert_eq!(try_next_with("C b A", &options), None);
+    assert_eq!(try_prev_with("C b A", &options), Some("cab".into()));
   }
 }
@@ -0,0 +1,467 @@
+use anagram::{
+  anagrams_with, count_big_with, is_anagram_with, occurences_in_reader_with, occurences_with,
+  try_next_with, try_prev_with, write_clusters, AnagramIndex, AnagramOptions, Case, Cluster,
+  ClusterFormat, PhraseSolver,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+  error::Error,
+  io::{self, BufRead, ErrorKind, Write},
+  path::PathBuf,
+  process,
+};
+
+type Result<T = (), E = Box<dyn Error>> = std::result::Result<T, E>;
+
+/// Exit code for a query whose answer is no, like `grep` finding nothing
+const EXIT_NO: i32 = 1;
+
+/// Exit code for bad arguments or failed input and output
+const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, Parser)]
+#[command(name = "anagram", version, about = "A collection of anagram utilities")]
+struct Arguments {
+  #[command(flatten)]
+  options:    Options,
+  #[command(subcommand)]
+  subcommand: Command,
+}
+
+#[derive(Debug, Args)]
+struct Options {
+  /// Ignore whitespace and punctuation and fold case
+  #[arg(long, global = true)]
+  phrase:           bool,
+  /// Ignore case
+  #[arg(long, short = 'i', global = true)]
+  ignore_case:      bool,
+  /// Remove accents and other combining marks
+  #[arg(long, global = true)]
+  strip_diacritics: bool,
+  /// Treat grapheme clusters rather than chars as letters
+  #[arg(long, global = true)]
+  graphemes:        bool,
+}
+
+impl Options {
+  fn anagram_options(&self) -> AnagramOptions {
+    let mut options = if self.phrase {
+      AnagramOptions::phrase()
+    } else {
+      AnagramOptions::new()
+    };
+
+    if self.ignore_case {
+      options = options.case(Case::Fold);
+    }
+
+    options
+      .strip_diacritics(self.strip_diacritics)
+      .graphemes(self.graphemes)
+  }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Count the distinct anagrams of each word
+  Count {
+    /// Words to count, read from stdin one per line if omitted
+    words: Vec<String>,
+  },
+  /// Check if every word is an anagram of the first, ignoring case like the
+  /// library's `is_anagram`, exiting with 1 if not
+  Check {
+    /// Words to compare, read from stdin one per line if omitted
+    words:          Vec<String>,
+    /// Treat upper and lower case letters as distinct
+    #[arg(long)]
+    case_sensitive: bool,
+  },
+  /// Print the next anagram of each word in lexicographic order, exiting with
+  /// 1 if a word is already the last one
+  Next {
+    /// Words to step, read from stdin one per line if omitted
+    words: Vec<String>,
+  },
+  /// Print the previous anagram of each word in lexicographic order, exiting
+  /// with 1 if a word is already the first one
+  Prev {
+    /// Words to step, read from stdin one per line if omitted
+    words: Vec<String>,
+  },
+  /// Print every distinct anagram of each word in lexicographic order
+  List {
+    /// Words to list, read from stdin one per line if omitted
+    words: Vec<String>,
+    /// Stop after this many anagrams of each word
+    #[arg(long, short)]
+    limit: Option<usize>,
+  },
+  /// Count the occurrences of anagrams of a pattern in a text, exiting with 1
+  /// if there are none
+  Occurrences {
+    /// The pattern to look for
+    pattern: String,
+    /// The text to search, read from stdin if omitted
+    text:    Option<String>,
+  },
+  /// Print every combination of dictionary words that together are an
+  /// anagram of a phrase, exiting with 1 if there are none
+  Solve {
+    /// The phrase to solve, with whitespace ignored
+    #[arg(required = true, value_name = "PHRASE")]
+    words:        Vec<String>,
+    /// A newline delimited word list
+    #[arg(long, short)]
+    dict:         PathBuf,
+    /// Only print solutions with at least this many words
+    #[arg(long, default_value_t = 1)]
+    min_words:    usize,
+    /// Only print solutions with at most this many words
+    #[arg(long)]
+    max_words:    Option<usize>,
+    /// Skip dictionary words shorter than this
+    #[arg(long, default_value_t = 1)]
+    min_word_len: usize,
+    /// Use a word in every solution
+    #[arg(long)]
+    require:      Vec<String>,
+    /// Never use a word
+    #[arg(long)]
+    exclude:      Vec<String>,
+    /// Stop after this many solutions
+    #[arg(long, short)]
+    limit:        Option<usize>,
+  },
+  /// Work with word lists
+  #[command(subcommand)]
+  Index(IndexCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexCommand {
+  /// Group a word list by signature and print every group
+  Build {
+    /// A newline delimited word list, read from stdin if omitted
+    dict:     Option<PathBuf>,
+    /// The output format
+    #[arg(long, short, value_enum, default_value_t = Format::Tsv)]
+    format:   Format,
+    /// Only print groups of two or more words, largest first
+    #[arg(long)]
+    clusters: bool,
+  },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+  Json,
+  Tsv,
+}
+
+impl From<Format> for ClusterFormat {
+  fn from(format: Format) -> Self {
+    match format {
+      Format::Json => ClusterFormat::Json,
+      Format::Tsv => ClusterFormat::Tsv,
+    }
+  }
+}
+
+/// Get the words given as arguments, or every non-blank line of stdin if
+/// there are none
+fn words(words: Vec<String>, stdin: impl BufRead) -> io::Result<Vec<String>> {
+  if !words.is_empty() {
+    return Ok(words);
+  }
+
+  stdin
+    .lines()
+    .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+    .map(|line| line.map(|line| line.trim_end_matches('\r').to_owned()))
+    .collect()
+}
+
+impl Arguments {
+  /// Run the subcommand, returning whether its answer was yes
+  fn run(self, stdin: impl BufRead, mut stdout: impl Write) -> Result<bool> {
+    let options = self.options.anagram_options();
+
+    match self.subcommand {
+      Command::Count { words: args } => {
+        for word in words(args, stdin)? {
+          writeln!(stdout, "{}", count_big_with(&word, &options))?;
+        }
+        Ok(true)
+      },
+      Command::Check {
+        words: args,
+        case_sensitive,
+      } => {
+        let words = words(args, stdin)?;
+
+        let options = if case_sensitive {
+          options.case(Case::Sensitive)
+        } else if self.options.ignore_case || self.options.phrase {
+          options
+        } else {
+          options.case(Case::Lower)
+        };
+
+        if words.len() < 2 {
+          return Err("`check` needs at least two words".into());
+        }
+
+        let ok = words[1..]
+          .iter()
+          .all(|word| is_anagram_with(&words[0], word, &options));
+        writeln!(stdout, "{}", ok)?;
+        Ok(ok)
+      },
+      Command::Next { words: args } => step(words(args, stdin)?, stdout, |word| {
+        try_next_with(word, &options)
+      }),
+      Command::Prev { words: args } => step(words(args, stdin)?, stdout, |word| {
+        try_prev_with(word, &options)
+      }),
+      Command::List { words: args, limit } => {
+        for word in words(args, stdin)? {
+          for anagram in anagrams_with(&word, &options).take(limit.unwrap_or(usize::MAX)) {
+            writeln!(stdout, "{}", anagram)?;
+          }
+        }
+        Ok(true)
+      },
+      Command::Occurrences { pattern, text } => {
+        let count = match text {
+          Some(text) => occurences_with(&text, &pattern, &options),
+          None => occurences_in_reader_with(stdin, &pattern, &options)?,
+        };
+
+        writeln!(stdout, "{}", count)?;
+        Ok(count > 0)
+      },
+      Command::Solve {
+        words,
+        dict,
+        min_words,
+        max_words,
+        min_word_len,
+        require,
+        exclude,
+        limit,
+      } => {
+        let index = AnagramIndex::from_file(&dict, options.ignore_whitespace(true))
+          .map_err(|error| format!("failed to read `{}`: {}", dict.display(), error))?;
+
+        let mut solver = PhraseSolver::new(&index)
+          .min_words(min_words)
+          .max_words(max_words.unwrap_or(usize::MAX))
+          .min_word_len(min_word_len);
+
+        for word in &require {
+          solver = solver.require(word);
+        }
+
+        for word in &exclude {
+          solver = solver.exclude(word);
+        }
+
+        let mut found = false;
+
+        for solution in solver
+          .solve(&words.join(" "))
+          .take(limit.unwrap_or(usize::MAX))
+        {
+          writeln!(stdout, "{}", solution.join(" "))?;
+          found = true;
+        }
+
+        Ok(found)
+      },
+      Command::Index(IndexCommand::Build {
+        dict,
+        format,
+        clusters,
+      }) => {
+        let index = match &dict {
+          Some(dict) => AnagramIndex::from_file(dict, options)
+            .map_err(|error| format!("failed to read `{}`: {}", dict.display(), error))?,
+          None => {
+            let mut index = AnagramIndex::with_options(options);
+            index.read_from(stdin)?;
+            index
+          },
+        };
+
+        let classes = if clusters {
+          index.clusters()
+        } else {
+          index
+            .classes()
+            .map(|class| Cluster {
+              signature: index.signature(&class[0]),
+              words:     class.to_vec(),
+            })
+            .collect()
+        };
+
+        write_clusters(&classes, format.into(), &mut stdout)?;
+        Ok(true)
+      },
+    }
+  }
+}
+
+/// Print the result of stepping each word, returning `false` if any word
+/// could not be stepped
+fn step(
+  words: Vec<String>,
+  mut stdout: impl Write,
+  step: impl Fn(&str) -> Option<String>,
+) -> Result<bool> {
+  let mut ok = true;
+
+  for word in words {
+    match step(&word) {
+      Some(stepped) => writeln!(stdout, "{}", stepped)?,
+      None => ok = false,
+    }
+  }
+
+  Ok(ok)
+}
+
+fn main() {
+  let arguments = Arguments::parse();
+
+  let stdin = io::stdin();
+  let stdout = io::stdout();
+
+  match arguments.run(stdin.lock(), stdout.lock()) {
+    Ok(true) => {},
+    Ok(false) => process::exit(EXIT_NO),
+    Err(error) => {
+      // output piped into something like `head` is not a failure
+      if let Some(error) = error.downcast_ref::<io::Error>() {
+        if error.kind() == ErrorKind::BrokenPipe {
+          return;
+        }
+      }
+
+      eprintln!("error: {}", error);
+      process::exit(EXIT_ERROR);
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn run(args: &[&str], stdin: &str) -> (bool, String) {
+    let arguments =
+      Arguments::try_parse_from(std::iter::once("anagram").chain(args.iter().copied())).unwrap();
+    let mut stdout = Vec::new();
+    let ok = arguments.run(stdin.as_bytes(), &mut stdout).unwrap();
+    (ok, String::from_utf8(stdout).unwrap())
+  }
+
+  #[test]
+  fn test_count() {
+    assert_eq!(
+      run(&["count", "ordeals", "aab"], ""),
+      (true, "5040\n3\n".into())
+    );
+    assert_eq!(run(&["count"], "aab\n\nab\n"), (true, "3\n2\n".into()));
+  }
+
+  #[test]
+  fn test_check() {
+    assert_eq!(
+      run(&["check", "listen", "silent"], ""),
+      (true, "true\n".into())
+    );
+    assert_eq!(
+      run(&["check", "listen", "Silent"], ""),
+      (true, "true\n".into())
+    );
+    assert_eq!(
+      run(&["check", "--case-sensitive", "listen", "Silent"], ""),
+      (false, "false\n".into())
+    );
+    assert_eq!(
+      run(&["check", "listen", "tinsel", "Silenz"], ""),
+      (false, "false\n".into())
+    );
+    assert_eq!(
+      run(&["check", "-i", "listen", "Silent"], ""),
+      (true, "true\n".into())
+    );
+    assert_eq!(
+      run(&["--phrase", "check"], "Dormitory\ndirty room\n"),
+      (true, "true\n".into())
+    );
+  }
+
+  #[test]
+  fn test_next_prev() {
+    assert_eq!(run(&["next", "abc"], ""), (true, "acb\n".into()));
+    assert_eq!(run(&["next", "abc", "cba"], ""), (false, "acb\n".into()));
+    assert_eq!(run(&["prev", "acb"], ""), (true, "abc\n".into()));
+    assert_eq!(run(&["prev", "abc"], ""), (false, "".into()));
+  }
+
+  #[test]
+  fn test_list() {
+    assert_eq!(run(&["list", "aab"], ""), (true, "aab\naba\nbaa\n".into()));
+    assert_eq!(run(&["list", "-l", "1", "cab"], ""), (true, "abc\n".into()));
+  }
+
+  #[test]
+  fn test_occurrences() {
+    assert_eq!(
+      run(&["occurrences", "ll", "hellollo"], ""),
+      (true, "2\n".into())
+    );
+    assert_eq!(run(&["occurrences", "xy"], "hello"), (false, "0\n".into()));
+  }
+
+  #[test]
+  fn test_index_build() {
+    assert_eq!(
+      run(&["index", "build"], "rat\ntar\ncat\n"),
+      (true, "art\trat\ttar\nact\tcat\n".into())
+    );
+    assert_eq!(
+      run(&["index", "build", "--clusters"], "rat\ntar\ncat\n"),
+      (true, "art\trat\ttar\n".into())
+    );
+
+    let path = std::env::temp_dir().join(format!("anagram-index-{}.txt", process::id()));
+    std::fs::write(&path, "Rat\ntar\n").unwrap();
+    assert_eq!(
+      run(&["-i", "index", "build", path.to_str().unwrap()], ""),
+      (true, "art\tRat\ttar\n".into())
+    );
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_solve() {
+    let path = std::env::temp_dir().join(format!("anagram-solve-{}.txt", process::id()));
+    std::fs::write(&path, "old\nwest\naction\nclint\neastwood\n").unwrap();
+    let dict = path.to_str().unwrap();
+
+    let (ok, output) = run(&["solve", "-d", dict, "clint", "eastwood"], "");
+    assert!(ok);
+    assert_eq!(output, "eastwood clint\naction west old\n");
+
+    let (ok, output) = run(&["solve", "-d", dict, "--min-words", "3", "rust"], "");
+    assert!(!ok);
+    assert_eq!(output, "");
+
+    std::fs::remove_file(&path).unwrap();
+  }
+}
@@ -0,0 +1,367 @@
+use crate::{
+  options::{Span, UnitCursor},
+  search::Symbols,
+  AnagramOptions,
+};
+use std::{
+  collections::{BTreeMap, HashMap, VecDeque},
+  iter::FusedIterator,
+  ops::Range,
+};
+
+/// Mix a symbol id into a well distributed hash, so the sum of the hashes of
+/// a window identifies its histogram with high probability
+fn mix(symbol: usize) -> u64 {
+  // splitmix64 finalizer
+  let mut z = (symbol as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+  z ^ (z >> 31)
+}
+
+/// The patterns sharing one histogram
+#[derive(Debug, Clone)]
+struct Signature {
+  counts:   Vec<(usize, u32)>,
+  patterns: Vec<usize>,
+}
+
+/// The signatures of every pattern with the same length, keyed by the sum of
+/// their symbol hashes
+#[derive(Debug, Clone)]
+struct Group {
+  len:        usize,
+  signatures: HashMap<u64, Vec<Signature>>,
+}
+
+/// Searches a text for anagrams of many patterns at once
+///
+/// Patterns are grouped by length, and patterns with the same histogram are
+/// grouped again, so the text is scanned a single time with one window per
+/// distinct pattern length
+/// Examples:
+/// MultiAnagramSearcher::new(["for", "of"]).find_iter("forof")
+/// -> "fo" at 0..2 matches pattern 1, "for" at 0..3 matches pattern 0,
+/// "of" at 3..5 matches pattern 1, "rof" at 2..5 matches pattern 0
+#[derive(Debug, Clone)]
+pub struct MultiAnagramSearcher {
+  options:  AnagramOptions,
+  symbols:  Symbols,
+  patterns: Vec<String>,
+  groups:   Vec<Group>,
+}
+
+impl MultiAnagramSearcher {
+  /// Create a searcher for the given patterns, comparing text exactly as
+  /// given
+  pub fn new<I, S>(patterns: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    Self::with_options(patterns, &AnagramOptions::new())
+  }
+
+  /// Create a searcher for the given patterns, normalizing patterns and text
+  /// with the given options
+  pub fn with_options<I, S>(patterns: I, options: &AnagramOptions) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    let mut symbols = Symbols::new();
+    let mut groups: BTreeMap<usize, HashMap<u64, Vec<Signature>>> = BTreeMap::new();
+    let mut originals = Vec::new();
+
+    for (index, pattern) in patterns.into_iter().enumerate() {
+      let pattern = pattern.as_ref();
+      originals.push(pattern.to_owned());
+
+      let units = symbols.intern_pattern(pattern, options);
+
+      // an empty pattern never matches
+      if units.is_empty() {
+        continue;
+      }
+
+      let mut counts: BTreeMap<usize, u32> = BTreeMap::new();
+      for &symbol in &units {
+        *counts.entry(symbol).or_insert(0) += 1;
+      }
+      let counts: Vec<(usize, u32)> = counts.into_iter().collect();
+
+      let hash = units
+        .iter()
+        .fold(0u64, |hash, &symbol| hash.wrapping_add(mix(symbol)));
+
+      let signatures = groups
+        .entry(units.len())
+        .or_default()
+        .entry(hash)
+        .or_default();
+
+      match signatures
+        .iter_mut()
+        .find(|signature| signature.counts == counts)
+      {
+        Some(signature) => signature.patterns.push(index),
+        None => signatures.push(Signature {
+          counts,
+          patterns: vec![index],
+        }),
+      }
+    }
+
+    Self {
+      options: options.clone(),
+      patterns: originals,
+      groups: groups
+        .into_iter()
+        .map(|(len, signatures)| Group { len, signatures })
+        .collect(),
+      symbols,
+    }
+  }
+
+  /// The patterns this searcher was built from, indexed by the ids reported
+  /// in matches
+  pub fn patterns(&self) -> &[String] {
+    &self.patterns
+  }
+
+  /// Find every window of a text that is an anagram of at least one pattern,
+  /// in the order the windows end and then from shortest to longest
+  pub fn find_iter<'s, 'a>(&'s self, text: &'a str) -> MultiMatches<'s, 'a> {
+    let symbols = self.symbols.len();
+    let longest = self.groups.last().map_or(0, |group| group.len);
+
+    MultiMatches {
+      units: UnitCursor::new(text),
+      recent: VecDeque::with_capacity(longest + 1),
+      batch: Vec::new(),
+      windows: self
+        .groups
+        .iter()
+        .map(|_| GroupWindow {
+          counts: vec![0; symbols],
+          hash:   0,
+        })
+        .collect(),
+      pending: VecDeque::new(),
+      done: false,
+      longest,
+      searcher: self,
+      text,
+    }
+  }
+
+  /// Count the occurences of anagrams of every pattern in a text
+  pub fn occurences(&self, text: &str) -> Vec<u128> {
+    let mut counts = vec![0; self.patterns.len()];
+
+    for found in self.find_iter(text) {
+      for &pattern in &found.patterns {
+        counts[pattern] += 1;
+      }
+    }
+
+    counts
+  }
+}
+
+/// An occurence of an anagram of one or more patterns in a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiMatch<'a> {
+  /// The byte offsets of the occurence in the text
+  pub bytes:    Range<usize>,
+  /// The char offsets of the occurence in the text
+  pub chars:    Range<usize>,
+  /// The matched substring, including any chars the options ignored
+  pub text:     &'a str,
+  /// The indices of every pattern the occurence is an anagram of
+  pub patterns: Vec<usize>,
+}
+
+/// The histogram and hash of the window for one pattern length
+#[derive(Debug, Clone)]
+struct GroupWindow {
+  counts: Vec<u32>,
+  hash:   u64,
+}
+
+/// An iterator over the occurences of anagrams of many patterns in a text,
+/// created by [`MultiAnagramSearcher::find_iter`]
+#[derive(Debug, Clone)]
+pub struct MultiMatches<'s, 'a> {
+  searcher: &'s MultiAnagramSearcher,
+  text:     &'a str,
+  units:    UnitCursor<'a>,
+  // the units of the longest window, along with where their raw units came
+  // from and whether each is the first unit its raw unit normalized to
+  recent:   VecDeque<(usize, Span, bool)>,
+  batch:    Vec<usize>,
+  longest:  usize,
+  windows:  Vec<GroupWindow>,
+  pending:  VecDeque<MultiMatch<'a>>,
+  done:     bool,
+}
+
+impl<'s, 'a> MultiMatches<'s, 'a> {
+  /// Slide every window over the units of the next raw unit, queueing
+  /// whatever matches, and return `false` at the end of the text
+  fn advance(&mut self) -> bool {
+    let (symbols, batch) = (&self.searcher.symbols, &mut self.batch);
+    batch.clear();
+
+    let last = match self.units.advance(&self.searcher.options, |unit, _| {
+      batch.push(symbols.get(unit))
+    }) {
+      Some(last) => last,
+      None => return false,
+    };
+
+    let groups = &self.searcher.groups;
+
+    for (i, &symbol) in self.batch.iter().enumerate() {
+      self.recent.push_back((symbol, last.clone(), i == 0));
+
+      for (group, window) in groups.iter().zip(&mut self.windows) {
+        // add last unit
+        window.counts[symbol] += 1;
+        window.hash = window.hash.wrapping_add(mix(symbol));
+
+        // remove first unit
+        if self.recent.len() > group.len {
+          let (first, _, _) = self.recent[self.recent.len() - 1 - group.len];
+          window.counts[first] -= 1;
+          window.hash = window.hash.wrapping_sub(mix(first));
+        }
+      }
+
+      if self.recent.len() > self.longest {
+        self.recent.pop_front();
+      }
+    }
+
+    // a raw unit that normalized to nothing leaves every window as it was
+    if self.batch.is_empty() {
+      return true;
+    }
+
+    for (group, window) in groups.iter().zip(&self.windows) {
+      if self.recent.len() < group.len {
+        continue;
+      }
+
+      // a window may not start part way through a raw unit
+      let first = match &self.recent[self.recent.len() - group.len] {
+        (_, first, true) => first,
+        _ => continue,
+      };
+
+      let candidates = match group.signatures.get(&window.hash) {
+        Some(candidates) => candidates,
+        None => continue,
+      };
+
+      // the window and the signature have the same length, so agreeing on
+      // every pattern symbol means agreeing everywhere
+      let signature = candidates.iter().find(|signature| {
+        signature
+          .counts
+          .iter()
+          .all(|&(symbol, count)| window.counts[symbol] == count)
+      });
+
+      if let Some(signature) = signature {
+        let bytes = first.bytes.start..last.bytes.end;
+
+        self.pending.push_back(MultiMatch {
+          text: &self.text[bytes.clone()],
+          chars: first.chars.start..last.chars.end,
+          patterns: signature.patterns.clone(),
+          bytes,
+        });
+      }
+    }
+
+    true
+  }
+}
+
+impl<'s, 'a> Iterator for MultiMatches<'s, 'a> {
+  type Item = MultiMatch<'a>;
+
+  fn next(&mut self) -> Option<MultiMatch<'a>> {
+    while self.pending.is_empty() && !self.done {
+      self.done = !self.advance();
+    }
+
+    self.pending.pop_front()
+  }
+}
+
+impl FusedIterator for MultiMatches<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{occurences, Normalization};
+
+  #[test]
+  fn test_find_iter() {
+    let searcher = MultiAnagramSearcher::new(["for", "of", "rof", "fo", ""]);
+    let matches: Vec<_> = searcher
+      .find_iter("forof")
+      .map(|found| (found.bytes, found.text, found.patterns))
+      .collect();
+    assert_eq!(matches, [
+      (0..2, "fo", vec![1, 3]),
+      (0..3, "for", vec![0, 2]),
+      (3..5, "of", vec![1, 3]),
+      (2..5, "rof", vec![0, 2]),
+    ]);
+  }
+
+  #[test]
+  fn test_find_iter_with_options() {
+    let searcher =
+      MultiAnagramSearcher::with_options(["Dormitory", "listen"], &AnagramOptions::phrase());
+    let matches: Vec<_> = searcher
+      .find_iter("Dirty room, silent night")
+      .map(|found| (found.text, found.patterns))
+      .collect();
+    assert_eq!(matches, [("Dirty room", vec![0]), ("silent", vec![1])]);
+
+    let searcher = MultiAnagramSearcher::with_options(["as", "ss"], &AnagramOptions::phrase());
+    let matches: Vec<_> = searcher
+      .find_iter("aß")
+      .map(|found| (found.text, found.patterns))
+      .collect();
+    assert_eq!(matches, [("ß", vec![1])]);
+
+    let nfc = AnagramOptions::new().normalization(Normalization::Nfc);
+    let searcher = MultiAnagramSearcher::with_options(["\u{e9}"], &nfc);
+    let matches: Vec<_> = searcher
+      .find_iter("xe\u{301}y")
+      .map(|found| found.text)
+      .collect();
+    assert_eq!(matches, ["e\u{301}"]);
+  }
+
+  #[test]
+  fn test_occurences() {
+    let text = "thegrandopeningscenerywasgreatforxxorfxdofr";
+    let patterns = ["for", "grand", "ee", "xyz", "greatfor", "e"];
+    let searcher = MultiAnagramSearcher::new(patterns);
+
+    assert_eq!(
+      searcher.occurences(text),
+      patterns
+        .iter()
+        .map(|pattern| occurences(text, pattern))
+        .collect::<Vec<_>>()
+    );
+    assert_eq!(searcher.patterns(), patterns);
+  }
+}
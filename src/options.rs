@@ -0,0 +1,446 @@
+use std::{borrow::Cow, ops::Range};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How letter case is treated when comparing text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+  /// Upper and lower case letters are distinct
+  Sensitive,
+  /// Letters are lowercased, so "A" and "a" are the same
+  Lower,
+  /// Letters are case folded, so "ß" and "SS" are also the same
+  Fold,
+}
+
+/// The Unicode normalization form text is converted to before comparing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+  /// Text is compared as is
+  None,
+  /// Canonical composition, "e\u{301}" becomes "é"
+  Nfc,
+  /// Canonical decomposition, "é" becomes "e\u{301}"
+  Nfd,
+  /// Compatibility composition, "ﬁ" becomes "fi"
+  Nfkc,
+  /// Compatibility decomposition
+  Nfkd,
+}
+
+/// How digits are treated when comparing text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Digits {
+  /// Digits are counted like any other char
+  Keep,
+  /// Digits are skipped
+  Ignore,
+}
+
+/// Controls how text is normalized before it is compared, counted or
+/// permuted by the `_with` functions
+///
+/// The default options compare text exactly as given
+/// Examples:
+/// AnagramOptions::new().ignore_whitespace(true).case(Case::Lower)
+/// "Dormitory" -> "dormitory"
+/// "Dirty room" -> "dirtyroom"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnagramOptions {
+  ignore_whitespace:  bool,
+  ignore_punctuation: bool,
+  strip_diacritics:   bool,
+  graphemes:          bool,
+  case:               Case,
+  normalization:      Normalization,
+  digits:             Digits,
+}
+
+impl Default for AnagramOptions {
+  fn default() -> Self {
+    Self {
+      ignore_whitespace:  false,
+      ignore_punctuation: false,
+      strip_diacritics:   false,
+      graphemes:          false,
+      case:               Case::Sensitive,
+      normalization:      Normalization::None,
+      digits:             Digits::Keep,
+    }
+  }
+}
+
+impl AnagramOptions {
+  /// Create options that compare text exactly as given
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Create options suited to phrase anagrams, ignoring case, whitespace and
+  /// punctuation
+  pub fn phrase() -> Self {
+    Self::new()
+      .ignore_whitespace(true)
+      .ignore_punctuation(true)
+      .case(Case::Fold)
+  }
+
+  /// Skip whitespace
+  pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+    self.ignore_whitespace = ignore_whitespace;
+    self
+  }
+
+  /// Skip punctuation
+  pub fn ignore_punctuation(mut self, ignore_punctuation: bool) -> Self {
+    self.ignore_punctuation = ignore_punctuation;
+    self
+  }
+
+  /// Remove accents and other combining marks, so "é" and "e" are the same
+  pub fn strip_diacritics(mut self, strip_diacritics: bool) -> Self {
+    self.strip_diacritics = strip_diacritics;
+    self
+  }
+
+  /// Treat extended grapheme clusters rather than chars as the units being
+  /// permuted, so emoji sequences and letters with combining accents are
+  /// never split apart
+  pub fn graphemes(mut self, graphemes: bool) -> Self {
+    self.graphemes = graphemes;
+    self
+  }
+
+  /// Set how letter case is treated
+  pub fn case(mut self, case: Case) -> Self {
+    self.case = case;
+    self
+  }
+
+  /// Set the Unicode normalization form
+  pub fn normalization(mut self, normalization: Normalization) -> Self {
+    self.normalization = normalization;
+    self
+  }
+
+  /// Set how digits are treated
+  pub fn digits(mut self, digits: Digits) -> Self {
+    self.digits = digits;
+    self
+  }
+
+  /// Normalize text according to these options
+  pub fn normalize(&self, text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    self.normalize_into(text, &mut out);
+    out
+  }
+
+  /// Split normalized text into the units being permuted, either chars or
+  /// grapheme clusters
+  pub(crate) fn units<'a>(&self, text: &'a str) -> Vec<&'a str> {
+    if self.graphemes {
+      text.graphemes(true).collect()
+    } else {
+      text
+        .char_indices()
+        .map(|(i, c)| &text[i..i + c.len_utf8()])
+        .collect()
+    }
+  }
+
+  /// Check if units are grapheme clusters rather than chars
+  pub(crate) fn uses_graphemes(&self) -> bool {
+    self.graphemes
+  }
+
+  /// Check if the normalization form composes chars, so a char has to be
+  /// normalized together with the marks that follow it
+  pub(crate) fn composes(&self) -> bool {
+    matches!(self.normalization, Normalization::Nfc | Normalization::Nfkc)
+  }
+
+  /// Split raw text into units, normalize each one on its own and call `f`
+  /// with every non-empty result along with where its unit came from
+  ///
+  /// In char mode a unit that normalizes to several chars, like "ß" when case
+  /// folding, is reported once per char with the same span
+  pub(crate) fn for_each_unit(&self, text: &str, mut f: impl FnMut(&str, Span)) {
+    let mut cursor = UnitCursor::new(text);
+    while cursor
+      .advance(self, |unit, span| f(unit, span.clone()))
+      .is_some()
+    {}
+  }
+
+  /// Normalize text according to these options, appending it to `out`
+  pub(crate) fn normalize_into(&self, text: &str, out: &mut String) {
+    let text = self.compose(text);
+
+    for c in text.chars() {
+      if self.is_ignored(c) {
+        continue;
+      }
+
+      match self.case {
+        Case::Sensitive => out.push(c),
+        Case::Lower => out.extend(c.to_lowercase()),
+        // mapping through upper case folds letters like "ß" that have no
+        // single char lower case equivalent
+        Case::Fold => out.extend(c.to_uppercase().flat_map(char::to_lowercase)),
+      }
+    }
+  }
+
+  /// Apply the normalization form and diacritic stripping
+  fn compose<'a>(&self, text: &'a str) -> Cow<'a, str> {
+    let compatibility = matches!(
+      self.normalization,
+      Normalization::Nfkc | Normalization::Nfkd
+    );
+
+    if self.strip_diacritics {
+      let stripped: String = if compatibility {
+        text.nfkd().filter(|&c| !is_combining_mark(c)).collect()
+      } else {
+        text.nfd().filter(|&c| !is_combining_mark(c)).collect()
+      };
+
+      // recompose whatever decomposed without a mark, like Hangul syllables
+      return match self.normalization {
+        Normalization::Nfd | Normalization::Nfkd => Cow::Owned(stripped),
+        _ => Cow::Owned(stripped.nfc().collect()),
+      };
+    }
+
+    match self.normalization {
+      Normalization::None => Cow::Borrowed(text),
+      Normalization::Nfc => Cow::Owned(text.nfc().collect()),
+      Normalization::Nfd => Cow::Owned(text.nfd().collect()),
+      Normalization::Nfkc => Cow::Owned(text.nfkc().collect()),
+      Normalization::Nfkd => Cow::Owned(text.nfkd().collect()),
+    }
+  }
+
+  fn is_ignored(&self, c: char) -> bool {
+    (self.ignore_whitespace && c.is_whitespace())
+      || (self.ignore_punctuation && is_punctuation(c))
+      || (self.digits == Digits::Ignore && c.is_numeric())
+  }
+}
+
+/// Where a unit of text came from, as byte and char offsets into the
+/// original text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Span {
+  pub(crate) bytes: Range<usize>,
+  pub(crate) chars: Range<usize>,
+}
+
+/// Walks raw text one char or grapheme cluster at a time, normalizing each
+/// unit on its own, so text can be scanned without splitting all of it up
+/// front
+///
+/// When a composing normalization form is selected a char is taken together
+/// with the combining marks after it, so "e\u{301}" still becomes "é"
+#[derive(Debug, Clone)]
+pub(crate) struct UnitCursor<'a> {
+  text:   &'a str,
+  bytes:  usize,
+  chars:  usize,
+  buffer: String,
+}
+
+impl<'a> UnitCursor<'a> {
+  pub(crate) fn new(text: &'a str) -> Self {
+    Self {
+      text,
+      bytes: 0,
+      chars: 0,
+      buffer: String::new(),
+    }
+  }
+
+  /// Normalize the next raw unit and call `f` with every non-empty unit it
+  /// becomes, returning where it came from, or `None` at the end of the text
+  pub(crate) fn advance(
+    &mut self,
+    options: &AnagramOptions,
+    mut f: impl FnMut(&str, &Span),
+  ) -> Option<Span> {
+    let rest = &self.text[self.bytes..];
+
+    let unit = if options.graphemes {
+      rest.graphemes(true).next()?
+    } else {
+      let mut chars = rest.char_indices();
+      let (_, c) = chars.next()?;
+
+      let len = if options.composes() {
+        chars
+          .find(|&(_, c)| !joins_previous(c))
+          .map_or(rest.len(), |(i, _)| i)
+      } else {
+        c.len_utf8()
+      };
+
+      &rest[..len]
+    };
+
+    let span = Span {
+      bytes: self.bytes..self.bytes + unit.len(),
+      chars: self.chars..self.chars + unit.chars().count(),
+    };
+
+    self.bytes = span.bytes.end;
+    self.chars = span.chars.end;
+
+    self.buffer.clear();
+    options.normalize_into(unit, &mut self.buffer);
+
+    if options.graphemes {
+      if !self.buffer.is_empty() {
+        f(&self.buffer, &span);
+      }
+    } else {
+      for (i, c) in self.buffer.char_indices() {
+        f(&self.buffer[i..i + c.len_utf8()], &span);
+      }
+    }
+
+    Some(span)
+  }
+}
+
+/// Check if a char can compose with the char before it, like a combining mark
+/// or a Hangul vowel or final consonant jamo
+fn joins_previous(c: char) -> bool {
+  is_combining_mark(c) || ('\u{1160}'..='\u{11ff}').contains(&c)
+}
+
+/// Check if a char is ASCII punctuation, which includes symbols like "+"
+/// and "$", or is in one of the Unicode punctuation categories
+fn is_punctuation(c: char) -> bool {
+  c.is_ascii_punctuation() || c.general_category_group() == GeneralCategoryGroup::Punctuation
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_normalize() {
+    assert_eq!(
+      AnagramOptions::new().normalize("Dirty room!"),
+      "Dirty room!"
+    );
+    assert_eq!(
+      AnagramOptions::phrase().normalize("Dirty room!"),
+      "dirtyroom"
+    );
+    assert_eq!(
+      AnagramOptions::phrase().normalize("“Don’t—stop”… ¿vale?"),
+      "dontstopvale"
+    );
+    assert_eq!(
+      AnagramOptions::phrase().normalize("نعم، لا। Ա՝ «Բ»"),
+      "نعملاաբ"
+    );
+  }
+
+  #[test]
+  fn test_normalize_case() {
+    let options = AnagramOptions::new();
+    assert_eq!(
+      options.clone().case(Case::Sensitive).normalize("Straße"),
+      "Straße"
+    );
+    assert_eq!(
+      options.clone().case(Case::Lower).normalize("Straße"),
+      "straße"
+    );
+    assert_eq!(options.case(Case::Fold).normalize("Straße"), "strasse");
+  }
+
+  #[test]
+  fn test_normalize_unicode() {
+    let options = AnagramOptions::new();
+    assert_eq!(
+      options
+        .clone()
+        .normalization(Normalization::Nfc)
+        .normalize("e\u{301}"),
+      "\u{e9}"
+    );
+    assert_eq!(
+      options
+        .clone()
+        .normalization(Normalization::Nfd)
+        .normalize("\u{e9}"),
+      "e\u{301}"
+    );
+    assert_eq!(
+      options
+        .clone()
+        .normalization(Normalization::Nfkc)
+        .normalize("ﬁ"),
+      "fi"
+    );
+    assert_eq!(
+      options
+        .clone()
+        .strip_diacritics(true)
+        .normalize("Crème brûlée, 한국"),
+      "Creme brulee, 한국"
+    );
+    assert_eq!(options.strip_diacritics(true).normalize("e\u{301}"), "e");
+  }
+
+  #[test]
+  fn test_units() {
+    let text = "ne\u{301}🇨🇦";
+    assert_eq!(AnagramOptions::new().units(text), [
+      "n",
+      "e",
+      "\u{301}",
+      "\u{1f1e8}",
+      "\u{1f1e6}"
+    ]);
+    assert_eq!(AnagramOptions::new().graphemes(true).units(text), [
+      "n", "e\u{301}", "🇨🇦"
+    ]);
+  }
+
+  #[test]
+  fn test_for_each_unit() {
+    let mut units = Vec::new();
+    AnagramOptions::phrase().for_each_unit("Aß, é", |unit, span| {
+      units.push((unit.to_string(), span.bytes, span.chars))
+    });
+    assert_eq!(units, [
+      ("a".into(), 0..1, 0..1),
+      ("s".into(), 1..3, 1..2),
+      ("s".into(), 1..3, 1..2),
+      ("é".into(), 5..7, 4..5),
+    ]);
+
+    let mut units = Vec::new();
+    AnagramOptions::new()
+      .graphemes(true)
+      .for_each_unit("ae\u{301}", |unit, span| {
+        units.push((unit.to_string(), span.bytes))
+      });
+    assert_eq!(units, [("a".into(), 0..1), ("e\u{301}".into(), 1..4)]);
+  }
+
+  #[test]
+  fn test_normalize_digits() {
+    let options = AnagramOptions::new();
+    assert_eq!(
+      options.clone().digits(Digits::Keep).normalize("r2d2"),
+      "r2d2"
+    );
+    assert_eq!(options.digits(Digits::Ignore).normalize("r2d2 ٣"), "rd ");
+  }
+}
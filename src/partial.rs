@@ -0,0 +1,202 @@
+use crate::{
+  arrangement::{distinct, Arrangements, Available, Backtrack},
+  multinomial_big, multiplicities, AnagramOptions,
+};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use std::iter::FusedIterator;
+
+/// Count the distinct strings of `k` letters that can be formed from the
+/// letters of a word, using each letter at most as many times as it occurs
+///
+/// Panics if the count does not fit in a `u128`, see `try_count_k` and
+/// `count_k_big` for long words
+/// Examples:
+/// ("abc", 2) -> 6
+/// ("aab", 2) -> 3, "aa", "ab" and "ba"
+/// ("ab", 3) -> 0
+pub fn count_k(word: &str, k: usize) -> u128 {
+  try_count_k(word, k).expect("anagram count overflowed u128, use `count_k_big` instead")
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word
+/// after normalizing it with the given options
+pub fn count_k_with(word: &str, k: usize, options: &AnagramOptions) -> u128 {
+  try_count_k_with(word, k, options)
+    .expect("anagram count overflowed u128, use `count_k_big` instead")
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word,
+/// returning `None` if the count does not fit in a `u128`
+pub fn try_count_k(word: &str, k: usize) -> Option<u128> {
+  count_k_big(word, k).to_u128()
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word
+/// after normalizing it with the given options, returning `None` on overflow
+pub fn try_count_k_with(word: &str, k: usize, options: &AnagramOptions) -> Option<u128> {
+  count_k_big_with(word, k, options).to_u128()
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word,
+/// exactly
+pub fn count_k_big(word: &str, k: usize) -> BigUint {
+  count_k_multiplicities(&multiplicities(word.chars()), k)
+}
+
+/// Count the distinct strings of `k` letters that can be formed from a word
+/// after normalizing it with the given options, exactly
+pub fn count_k_big_with(word: &str, k: usize, options: &AnagramOptions) -> BigUint {
+  let normalized = options.normalize(word);
+  count_k_multiplicities(&multiplicities(options.units(&normalized)), k)
+}
+
+/// Count the arrangements of `k` units drawn from units with the given
+/// multiplicities
+fn count_k_multiplicities(counts: &[usize], k: usize) -> BigUint {
+  if k > counts.iter().sum() {
+    return BigUint::zero();
+  }
+
+  // ways[j] counts the strings of length j using the units seen so far
+  let mut ways = vec![BigUint::zero(); k + 1];
+  ways[0] = 1u32.into();
+
+  for &count in counts {
+    let mut next = ways.clone();
+
+    for (len, ways) in ways.iter().enumerate().filter(|(_, ways)| !ways.is_zero()) {
+      for copies in 1..=count.min(k - len) {
+        // interleave the copies with the units already placed
+        next[len + copies] += ways * multinomial_big(&[len, copies]);
+      }
+    }
+
+    ways = next;
+  }
+
+  ways.pop().unwrap()
+}
+
+/// Iterate over every distinct string of `k` letters that can be formed from
+/// the letters of a word, in lexicographic order
+///
+/// Examples:
+/// ("aab", 2) -> "aa", "ab", "ba"
+/// ("cab", 1) -> "a", "b", "c"
+pub fn partial_anagrams(word: &str, k: usize) -> PartialAnagrams {
+  PartialAnagrams::new(&AnagramOptions::new().units(word), k)
+}
+
+/// Iterate over every distinct string of `k` letters that can be formed from
+/// a word after normalizing it with the given options, in lexicographic order
+pub fn partial_anagrams_with(word: &str, k: usize, options: &AnagramOptions) -> PartialAnagrams {
+  PartialAnagrams::new(&options.units(&options.normalize(word)), k)
+}
+
+/// A lazy iterator over the distinct `k` letter strings that can be formed
+/// from a word, created by [`partial_anagrams`]
+#[derive(Debug, Clone)]
+pub struct PartialAnagrams {
+  arrangements: Arrangements<Backtrack<Available>>,
+}
+
+impl PartialAnagrams {
+  /// Track how many of each distinct unit are left over, so each step only
+  /// moves integers around
+  fn new(units: &[&str], k: usize) -> Self {
+    let (distinct, indices) = distinct(units);
+
+    let mut available = vec![0; distinct.len()];
+    for i in indices {
+      available[i] += 1;
+    }
+
+    // only start filling once `k` is known to be no more than the unit count
+    let step = if k > units.len() {
+      None
+    } else {
+      Some(Backtrack::new(distinct.len(), Available::new(available, k)))
+    };
+
+    Self {
+      arrangements: Arrangements::new(distinct, step),
+    }
+  }
+
+  /// Write the next arrangement into `buffer`, reusing its allocation, and
+  /// return `false` once every arrangement has been produced
+  pub fn next_into(&mut self, buffer: &mut String) -> bool {
+    self.arrangements.next_into(buffer)
+  }
+}
+
+impl Iterator for PartialAnagrams {
+  type Item = String;
+
+  fn next(&mut self) -> Option<String> {
+    self.arrangements.next_string()
+  }
+}
+
+impl FusedIterator for PartialAnagrams {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::count;
+
+  #[test]
+  fn test_count_k() {
+    assert_eq!(count_k("abc", 2), 6);
+    assert_eq!(count_k("aab", 2), 3);
+    assert_eq!(count_k("aab", 0), 1);
+    assert_eq!(count_k("ab", 3), 0);
+    assert_eq!(count_k("ab", usize::MAX), 0);
+    assert_eq!(count_k("", 0), 1);
+    assert_eq!(count_k("mississippi", 11), count("mississippi"));
+    assert_eq!(count_k("mississippi", 2), 4 * 4 - 1);
+    assert_eq!(count_k_with("AaB", 2, &AnagramOptions::phrase()), 3);
+  }
+
+  #[test]
+  fn test_count_k_big() {
+    let word = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJ";
+    assert_eq!(try_count_k(word, 35), None);
+    assert_eq!(try_count_k(word, 3), Some(36 * 35 * 34));
+    assert_eq!(count_k_big(word, 36), crate::count_big(word));
+  }
+
+  #[test]
+  fn test_partial_anagrams() {
+    assert_eq!(partial_anagrams("aab", 2).collect::<Vec<_>>(), [
+      "aa", "ab", "ba"
+    ]);
+    assert_eq!(partial_anagrams("cab", 1).collect::<Vec<_>>(), [
+      "a", "b", "c"
+    ]);
+    assert_eq!(partial_anagrams("ab", 0).collect::<Vec<_>>(), [""]);
+    assert_eq!(partial_anagrams("ab", 3).count(), 0);
+    assert_eq!(partial_anagrams("ab", usize::MAX).count(), 0);
+    assert_eq!(
+      partial_anagrams("bca", 3).collect::<Vec<_>>(),
+      crate::anagrams("bca").collect::<Vec<_>>()
+    );
+
+    for k in 0..=11 {
+      let all: Vec<_> = partial_anagrams("mississippi", k).collect();
+      assert_eq!(all.len() as u128, count_k("mississippi", k));
+      assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+  }
+
+  #[test]
+  fn test_partial_anagrams_with() {
+    let options = AnagramOptions::new().graphemes(true);
+    assert_eq!(partial_anagrams_with("e\u{301}ab", 2, &options).count(), 6);
+    assert_eq!(
+      partial_anagrams_with("Ba, a", 2, &AnagramOptions::phrase()).collect::<Vec<_>>(),
+      ["aa", "ab", "ba"]
+    );
+  }
+}
@@ -0,0 +1,130 @@
+/// Rearrange `items` into the next lexicographically greater permutation,
+/// returning `false` and leaving `items` sorted if they were already the
+/// greatest permutation
+///
+/// Works in place without allocating, and equal items are never swapped with
+/// each other, so every distinct arrangement of a multiset is visited once
+/// Examples:
+/// [1, 2, 3] -> [1, 3, 2], true
+/// [1, 2, 2] -> [2, 1, 2], true
+/// [3, 2, 1] -> [1, 2, 3], false
+pub fn next_permutation<T: Ord>(items: &mut [T]) -> bool {
+  if items.len() < 2 {
+    return false;
+  }
+
+  // find the start of the longest non-increasing suffix
+  let mut i = items.len() - 1;
+  while i > 0 && items[i - 1] >= items[i] {
+    i -= 1;
+  }
+
+  if i == 0 {
+    items.reverse();
+    return false;
+  }
+
+  // find the rightmost item greater than the pivot
+  let mut j = items.len() - 1;
+  while items[j] <= items[i - 1] {
+    j -= 1;
+  }
+
+  items.swap(i - 1, j);
+  items[i..].reverse();
+
+  true
+}
+
+/// Rearrange `items` into the previous lexicographically smaller
+/// permutation, returning `false` and leaving `items` reverse sorted if they
+/// were already the smallest permutation
+///
+/// Works in place without allocating, and equal items are never swapped with
+/// each other, so every distinct arrangement of a multiset is visited once
+/// Examples:
+/// [1, 3, 2] -> [1, 2, 3], true
+/// [2, 1, 2] -> [1, 2, 2], true
+/// [1, 2, 3] -> [3, 2, 1], false
+pub fn prev_permutation<T: Ord>(items: &mut [T]) -> bool {
+  if items.len() < 2 {
+    return false;
+  }
+
+  // find the start of the longest non-decreasing suffix
+  let mut i = items.len() - 1;
+  while i > 0 && items[i - 1] <= items[i] {
+    i -= 1;
+  }
+
+  if i == 0 {
+    items.reverse();
+    return false;
+  }
+
+  // find the rightmost item smaller than the pivot
+  let mut j = items.len() - 1;
+  while items[j] >= items[i - 1] {
+    j -= 1;
+  }
+
+  items.swap(i - 1, j);
+  items[i..].reverse();
+
+  true
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::count;
+
+  #[test]
+  fn test_next_permutation() {
+    let mut items = vec![1u32, 2, 3];
+    assert!(next_permutation(&mut items));
+    assert_eq!(items, [1, 3, 2]);
+
+    let mut items = [3, 2, 1];
+    assert!(!next_permutation(&mut items));
+    assert_eq!(items, [1, 2, 3]);
+
+    let mut bytes = *b"aab";
+    let mut seen = vec![bytes];
+    while next_permutation(&mut bytes) {
+      seen.push(bytes);
+    }
+    assert_eq!(seen, [*b"aab", *b"aba", *b"baa"]);
+
+    let mut items: Vec<char> = "mississippi".chars().collect();
+    items.sort_unstable();
+    let mut total = 1;
+    while next_permutation(&mut items) {
+      total += 1;
+    }
+    assert_eq!(total, count("mississippi"));
+
+    assert!(!next_permutation::<u8>(&mut []));
+    assert!(!next_permutation(&mut [1]));
+  }
+
+  #[test]
+  fn test_prev_permutation() {
+    let mut items = vec![1u32, 3, 2];
+    assert!(prev_permutation(&mut items));
+    assert_eq!(items, [1, 2, 3]);
+
+    let mut items = [1, 2, 3];
+    assert!(!prev_permutation(&mut items));
+    assert_eq!(items, [3, 2, 1]);
+
+    let mut items = ["b", "a", "a"];
+    assert!(prev_permutation(&mut items));
+    assert_eq!(items, ["a", "b", "a"]);
+
+    let mut items = [2, 1, 2, 1];
+    assert!(next_permutation(&mut items));
+    assert!(prev_permutation(&mut items));
+    assert_eq!(items, [2, 1, 2, 1]);
+  }
+}
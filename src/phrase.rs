@@ -0,0 +1,419 @@
+use crate::AnagramIndex;
+use std::{cmp::Reverse, collections::HashMap, iter::FusedIterator};
+
+/// Find every combination of words in a dictionary whose letters together are
+/// exactly the letters of a phrase
+///
+/// Examples:
+/// ("clint eastwood", ["old", "west", "action", "eastwood", "clint"])
+/// -> ["eastwood", "clint"], ["action", "west", "old"]
+pub fn phrase_anagrams<'a>(phrase: &str, dict: &'a AnagramIndex) -> PhraseAnagrams<'a> {
+  PhraseSolver::new(dict).solve(phrase)
+}
+
+/// Solves phrases into combinations of dictionary words by backtracking over
+/// the letter signatures of the words that fit in the phrase
+///
+/// Solutions come back in a fixed order: each solution lists its words from
+/// longest to shortest, ties broken by signature and then by the order the
+/// words were inserted into the dictionary
+/// Examples:
+/// PhraseSolver::new(&dict).exclude("clint").solve("clint eastwood")
+/// -> ["action", "west", "old"]
+#[derive(Debug, Clone)]
+pub struct PhraseSolver<'a> {
+  index:        &'a AnagramIndex,
+  min_words:    usize,
+  max_words:    usize,
+  min_word_len: usize,
+  required:     Vec<String>,
+  excluded:     Vec<String>,
+}
+
+impl<'a> PhraseSolver<'a> {
+  /// Create a solver over a dictionary, normalizing phrases with the
+  /// dictionary's options
+  pub fn new(index: &'a AnagramIndex) -> Self {
+    Self {
+      index,
+      min_words: 1,
+      max_words: usize::MAX,
+      min_word_len: 1,
+      required: Vec::new(),
+      excluded: Vec::new(),
+    }
+  }
+
+  /// Only find solutions with at least this many words, counting required
+  /// words
+  pub fn min_words(mut self, min_words: usize) -> Self {
+    self.min_words = min_words;
+    self
+  }
+
+  /// Only find solutions with at most this many words, counting required
+  /// words
+  pub fn max_words(mut self, max_words: usize) -> Self {
+    self.max_words = max_words;
+    self
+  }
+
+  /// Skip dictionary words with fewer than this many letters
+  pub fn min_word_len(mut self, min_word_len: usize) -> Self {
+    self.min_word_len = min_word_len;
+    self
+  }
+
+  /// Start every solution with a word, which need not be in the dictionary
+  pub fn require(mut self, word: &str) -> Self {
+    self.required.push(word.to_owned());
+    self
+  }
+
+  /// Never use a dictionary word, compared after normalization
+  pub fn exclude(mut self, word: &str) -> Self {
+    self.excluded.push(self.index.options().normalize(word));
+    self
+  }
+
+  /// Find every solution for a phrase, lazily, so a search with a huge number
+  /// of solutions can be cut off at any point
+  pub fn solve(&self, phrase: &str) -> PhraseAnagrams<'a> {
+    let mut counts = self.index.unit_counts(phrase);
+
+    // the letters of required words are spoken for
+    for word in &self.required {
+      for (unit, n) in self.index.unit_counts(word) {
+        match counts.get_mut(&unit) {
+          Some(count) if *count >= n => *count -= n,
+          _ => return PhraseAnagrams::empty(self.required.clone()),
+        }
+      }
+    }
+
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut remaining = Vec::new();
+
+    for (unit, &count) in &counts {
+      ids.insert(unit.clone(), remaining.len());
+      remaining.push(count);
+    }
+
+    let options = self.index.options();
+
+    let mut candidates: Vec<Candidate<'a>> = self
+      .index
+      .sub_anagram_classes(&mut counts)
+      .into_iter()
+      .filter_map(|class| {
+        let words: Vec<&'a str> = class
+          .iter()
+          .map(String::as_str)
+          .filter(|word| !self.excluded.contains(&options.normalize(word)))
+          .collect();
+
+        let counts = self.index.unit_counts(words.first()?);
+        let len = counts.values().sum();
+
+        if len < self.min_word_len {
+          return None;
+        }
+
+        let mut counts: Vec<(usize, usize)> =
+          counts.iter().map(|(unit, &n)| (ids[unit], n)).collect();
+        counts.sort_unstable();
+
+        Some(Candidate { counts, len, words })
+      })
+      .collect();
+
+    // the sort is stable, so ties stay in signature order
+    candidates.sort_by_key(|candidate| Reverse(candidate.len));
+
+    PhraseAnagrams {
+      total: remaining.iter().sum(),
+      min_words: self.min_words.saturating_sub(self.required.len()),
+      max_words: match self.max_words.checked_sub(self.required.len()) {
+        Some(max_words) => max_words,
+        None => return PhraseAnagrams::empty(self.required.clone()),
+      },
+      required: self.required.clone(),
+      chosen: Vec::new(),
+      cursor: 0,
+      picks: None,
+      done: false,
+      candidates,
+      remaining,
+    }
+  }
+}
+
+/// A group of dictionary words sharing a signature that fits in the phrase
+#[derive(Debug, Clone)]
+struct Candidate<'a> {
+  counts: Vec<(usize, usize)>,
+  len:    usize,
+  words:  Vec<&'a str>,
+}
+
+/// An iterator over the solutions of a phrase, created by
+/// [`PhraseSolver::solve`]
+#[derive(Debug, Clone)]
+pub struct PhraseAnagrams<'a> {
+  candidates: Vec<Candidate<'a>>,
+  required:   Vec<String>,
+  min_words:  usize,
+  max_words:  usize,
+  remaining:  Vec<usize>,
+  total:      usize,
+  chosen:     Vec<usize>,
+  cursor:     usize,
+  picks:      Option<Vec<usize>>,
+  done:       bool,
+}
+
+impl<'a> PhraseAnagrams<'a> {
+  /// An iterator with no solutions
+  fn empty(required: Vec<String>) -> Self {
+    Self {
+      candidates: Vec::new(),
+      min_words: 0,
+      max_words: 0,
+      remaining: Vec::new(),
+      total: 0,
+      chosen: Vec::new(),
+      cursor: 0,
+      picks: None,
+      done: true,
+      required,
+    }
+  }
+
+  /// Check if a candidate's letters are all still available
+  fn fits(&self, candidate: &Candidate) -> bool {
+    candidate.len <= self.total
+      && candidate
+        .counts
+        .iter()
+        .all(|&(unit, n)| self.remaining[unit] >= n)
+  }
+
+  /// Take a candidate's letters out of the phrase, or put them back
+  fn take(&mut self, candidate: usize, put_back: bool) {
+    let candidate = &self.candidates[candidate];
+
+    for &(unit, n) in &candidate.counts {
+      if put_back {
+        self.remaining[unit] += n;
+      } else {
+        self.remaining[unit] -= n;
+      }
+    }
+
+    if put_back {
+      self.total += candidate.len;
+    } else {
+      self.total -= candidate.len;
+    }
+  }
+
+  /// Advance the backtracking search to the next multiset of signatures that
+  /// uses up the phrase exactly, returning `false` once there are no more
+  ///
+  /// Signatures are chosen in candidate order, repeats allowed, so every
+  /// multiset is visited exactly once
+  fn next_signatures(&mut self) -> bool {
+    while !self.done {
+      // required words alone may use up the phrase
+      if self.total == 0 && self.chosen.is_empty() && self.picks.is_none() {
+        self.done = true;
+        return !self.required.is_empty() && self.min_words == 0;
+      }
+
+      if self.cursor < self.candidates.len() && self.chosen.len() < self.max_words {
+        if self.fits(&self.candidates[self.cursor]) {
+          self.take(self.cursor, false);
+          self.chosen.push(self.cursor);
+
+          if self.total == 0 && self.chosen.len() >= self.min_words {
+            return true;
+          }
+        } else {
+          self.cursor += 1;
+        }
+
+        continue;
+      }
+
+      match self.chosen.pop() {
+        Some(last) => {
+          self.take(last, true);
+          self.cursor = last + 1;
+        },
+        None => self.done = true,
+      }
+    }
+
+    false
+  }
+
+  /// Advance to the next choice of words for the current signatures, where
+  /// words picked for a repeated signature never decrease, returning `false`
+  /// once every choice was made
+  fn next_picks(picks: &mut [usize], chosen: &[usize], candidates: &[Candidate]) -> bool {
+    for i in (0..picks.len()).rev() {
+      if picks[i] + 1 < candidates[chosen[i]].words.len() {
+        picks[i] += 1;
+
+        for j in i + 1..picks.len() {
+          picks[j] = if chosen[j] == chosen[j - 1] {
+            picks[j - 1]
+          } else {
+            0
+          };
+        }
+
+        return true;
+      }
+    }
+
+    false
+  }
+}
+
+impl<'a> Iterator for PhraseAnagrams<'a> {
+  type Item = Vec<String>;
+
+  fn next(&mut self) -> Option<Vec<String>> {
+    let advanced = match &mut self.picks {
+      Some(picks) => Self::next_picks(picks, &self.chosen, &self.candidates),
+      None => false,
+    };
+
+    if !advanced {
+      if !self.next_signatures() {
+        self.picks = None;
+        return None;
+      }
+      self.picks = Some(vec![0; self.chosen.len()]);
+    }
+
+    let picks = self.picks.as_ref().unwrap();
+
+    let mut words = self.required.clone();
+    words.extend(
+      self
+        .chosen
+        .iter()
+        .zip(picks)
+        .map(|(&candidate, &pick)| self.candidates[candidate].words[pick].to_owned()),
+    );
+
+    Some(words)
+  }
+}
+
+impl FusedIterator for PhraseAnagrams<'_> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::AnagramOptions;
+
+  fn dict() -> AnagramIndex {
+    let mut index = AnagramIndex::with_options(AnagramOptions::phrase());
+    index.extend(&[
+      "old", "west", "action", "eastwood", "clint", "a", "cat", "lint", "stew", "owed", "cold",
+      "toe", "Lo",
+    ]);
+    index
+  }
+
+  #[test]
+  fn test_phrase_anagrams() {
+    let dict = dict();
+    let solutions: Vec<_> = phrase_anagrams("Clint Eastwood", &dict).collect();
+
+    assert!(solutions.contains(&vec![
+      "action".to_owned(),
+      "west".to_owned(),
+      "old".to_owned()
+    ]));
+    assert!(solutions.contains(&vec!["eastwood".to_owned(), "clint".to_owned()]));
+    assert!(solutions.contains(&vec![
+      "action".to_owned(),
+      "stew".to_owned(),
+      "old".to_owned()
+    ]));
+    assert_eq!(solutions[0], ["eastwood", "clint"]);
+
+    for solution in &solutions {
+      assert_eq!(
+        dict.signature(&solution.concat()),
+        dict.signature("clint eastwood")
+      );
+    }
+
+    assert_eq!(phrase_anagrams("", &dict).count(), 0);
+    assert_eq!(phrase_anagrams("zzz", &dict).count(), 0);
+    assert_eq!(phrase_anagrams("aa", &dict).collect::<Vec<_>>(), [[
+      "a", "a"
+    ]]);
+  }
+
+  #[test]
+  fn test_phrase_solver() {
+    let dict = dict();
+
+    let solutions: Vec<_> = PhraseSolver::new(&dict)
+      .min_words(3)
+      .max_words(3)
+      .min_word_len(3)
+      .exclude("STEW")
+      .solve("clint eastwood")
+      .collect();
+    assert_eq!(solutions, [["action", "west", "old"]]);
+
+    let solutions: Vec<_> = PhraseSolver::new(&dict)
+      .require("clint")
+      .solve("clint eastwood")
+      .collect();
+    assert_eq!(solutions[0], ["clint", "eastwood"]);
+    assert!(solutions.iter().all(|solution| solution[0] == "clint"));
+
+    assert_eq!(
+      PhraseSolver::new(&dict)
+        .require("Eastwood")
+        .require("Clint")
+        .solve("clint eastwood")
+        .collect::<Vec<_>>(),
+      [["Eastwood", "Clint"]]
+    );
+    assert_eq!(
+      PhraseSolver::new(&dict)
+        .require("zebra")
+        .solve("clint eastwood")
+        .count(),
+      0
+    );
+    assert_eq!(
+      PhraseSolver::new(&dict)
+        .max_words(1)
+        .solve("clint eastwood")
+        .count(),
+      0
+    );
+  }
+
+  #[test]
+  fn test_phrase_anagrams_lazy() {
+    let dict: AnagramIndex = ["a", "b", "ab", "ba"].iter().collect();
+    let phrase = "ab".repeat(64);
+
+    let solutions: Vec<_> = phrase_anagrams(&phrase, &dict).take(3).collect();
+    assert_eq!(solutions.len(), 3);
+    assert!(solutions
+      .iter()
+      .all(|solution| solution.concat().len() == 128));
+  }
+}
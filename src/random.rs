@@ -0,0 +1,253 @@
+use crate::{anagrams_with, count_arrangements_big, AnagramOptions};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use rand::{seq::SliceRandom, Rng};
+use std::{borrow::Cow, collections::HashSet};
+
+/// The most anagrams `Sampler::sample_many` reserves room for up front, any
+/// more are made room for as they are drawn
+const MAX_RESERVE: usize = 1024;
+
+/// Pick one of the distinct anagrams of a word uniformly at random
+///
+/// Every distinct anagram corresponds to the same number of orderings of the
+/// word's chars, so shuffling the chars never favours one arrangement over
+/// another, even when letters repeat
+pub fn random_anagram<R: Rng + ?Sized>(word: &str, rng: &mut R) -> String {
+  let mut chars: Vec<char> = word.chars().collect();
+  chars.shuffle(rng);
+  chars.into_iter().collect()
+}
+
+/// Pick one of the distinct anagrams of a word uniformly at random after
+/// normalizing it with the given options, shuffling grapheme clusters if the
+/// options ask for them
+pub fn random_anagram_with<R: Rng + ?Sized>(
+  word: &str,
+  options: &AnagramOptions,
+  rng: &mut R,
+) -> String {
+  let normalized = options.normalize(word);
+  let mut units = options.units(&normalized);
+  units.shuffle(rng);
+  units.concat()
+}
+
+/// Draws uniformly distributed distinct anagrams of a word
+///
+/// Examples:
+/// Sampler::new("ab").exclude_identity(true).sample(rng) -> Some("ba")
+/// Sampler::new("aa").exclude_identity(true).sample(rng) -> None
+#[derive(Debug, Clone)]
+pub struct Sampler<'a> {
+  word:             Cow<'a, str>,
+  units:            AnagramOptions,
+  exclude_identity: bool,
+}
+
+impl<'a> Sampler<'a> {
+  /// Create a sampler over the anagrams of `word`
+  pub fn new(word: &'a str) -> Self {
+    Self {
+      word:             Cow::Borrowed(word),
+      units:            AnagramOptions::new(),
+      exclude_identity: false,
+    }
+  }
+
+  /// Create a sampler over the anagrams of `word` after normalizing it with
+  /// the given options
+  pub fn with_options(word: &str, options: &AnagramOptions) -> Sampler<'static> {
+    Sampler {
+      word:             Cow::Owned(options.normalize(word)),
+      // the word is already normalized, so only the choice of units is kept
+      units:            AnagramOptions::new().graphemes(options.uses_graphemes()),
+      exclude_identity: false,
+    }
+  }
+
+  /// Never produce the original word itself
+  pub fn exclude_identity(mut self, exclude_identity: bool) -> Self {
+    self.exclude_identity = exclude_identity;
+    self
+  }
+
+  /// The number of anagrams this sampler can produce
+  pub fn population(&self) -> BigUint {
+    let count = count_arrangements_big(self.units.units(&self.word));
+
+    if self.exclude_identity {
+      count - BigUint::one()
+    } else {
+      count
+    }
+  }
+
+  /// Draw a single anagram, or `None` if there are none to draw from
+  pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+    if self.population().is_zero() {
+      None
+    } else {
+      Some(self.draw(rng))
+    }
+  }
+
+  /// Draw a single anagram from a non-empty population
+  fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+    // with at least two anagrams the identity is drawn at most half the
+    // time, so this takes two attempts on average
+    loop {
+      let anagram = random_anagram_with(&self.word, &self.units, rng);
+
+      if !self.exclude_identity || anagram != self.word {
+        return anagram;
+      }
+    }
+  }
+
+  /// Draw `k` distinct anagrams without replacement, or every anagram in a
+  /// random order if there are fewer than `k`
+  pub fn sample_many<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Vec<String> {
+    let population = self.population();
+
+    if population <= BigUint::from(k) * 2u32 {
+      // the population is small, so pick from all of it
+      let mut all: Vec<String> = anagrams_with(&self.word, &self.units)
+        .filter(|anagram| !self.exclude_identity || **anagram != *self.word)
+        .collect();
+
+      let amount = k.min(all.len());
+      let (chosen, _) = all.partial_shuffle(rng, amount);
+
+      return chosen.to_vec();
+    }
+
+    // the population is at least twice `k`, so every draw is new with
+    // probability at least one half
+    let capacity = population
+      .to_usize()
+      .map_or(k, |population| k.min(population))
+      .min(MAX_RESERVE);
+
+    let mut seen = HashSet::with_capacity(capacity);
+    let mut sample = Vec::with_capacity(capacity);
+
+    while sample.len() < k {
+      let anagram = self.draw(rng);
+
+      if seen.insert(anagram.clone()) {
+        sample.push(anagram);
+      }
+    }
+
+    sample
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{rngs::StdRng, SeedableRng};
+  use std::collections::HashMap;
+
+  #[test]
+  fn test_random_anagram() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut seen = HashMap::new();
+
+    for _ in 0..3000 {
+      *seen.entry(random_anagram("aab", &mut rng)).or_insert(0) += 1;
+    }
+
+    assert_eq!(seen.len(), 3);
+    assert!(seen.values().all(|&n| (850..1150).contains(&n)));
+    assert_eq!(random_anagram("", &mut rng), "");
+  }
+
+  #[test]
+  fn test_random_anagram_with() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let anagram = random_anagram_with("A a!", &AnagramOptions::phrase(), &mut rng);
+    assert_eq!(anagram, "aa");
+
+    assert_eq!(
+      Sampler::with_options("A, a", &AnagramOptions::phrase())
+        .exclude_identity(true)
+        .sample(&mut rng),
+      None
+    );
+  }
+
+  #[test]
+  fn test_sampler_graphemes() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let sampler = Sampler::with_options("e\u{301}a", &AnagramOptions::new().graphemes(true));
+    assert_eq!(sampler.population(), BigUint::from(2u32));
+
+    let mut sample = sampler.sample_many(5, &mut rng);
+    sample.sort();
+    assert_eq!(sample, ["ae\u{301}", "e\u{301}a"]);
+    assert_eq!(
+      sampler.exclude_identity(true).sample(&mut rng),
+      Some("ae\u{301}".into())
+    );
+  }
+
+  #[test]
+  fn test_sampler_sample() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..100 {
+      assert_eq!(
+        Sampler::new("ab").exclude_identity(true).sample(&mut rng),
+        Some("ba".into())
+      );
+    }
+
+    assert_eq!(Sampler::new("aa").sample(&mut rng), Some("aa".into()));
+    assert_eq!(
+      Sampler::new("aa").exclude_identity(true).sample(&mut rng),
+      None
+    );
+    assert_eq!(
+      Sampler::new("").exclude_identity(true).sample(&mut rng),
+      None
+    );
+  }
+
+  #[test]
+  fn test_sampler_sample_many() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut sample = Sampler::new("abc").sample_many(10, &mut rng);
+    sample.sort();
+    assert_eq!(sample, ["abc", "acb", "bac", "bca", "cab", "cba"]);
+
+    let mut sample = Sampler::new("aab")
+      .exclude_identity(true)
+      .sample_many(5, &mut rng);
+    sample.sort();
+    assert_eq!(sample, ["aba", "baa"]);
+
+    let sample = Sampler::new("ordeals")
+      .exclude_identity(true)
+      .sample_many(100, &mut rng);
+    assert_eq!(sample.len(), 100);
+    assert_eq!(sample.iter().collect::<HashSet<_>>().len(), 100);
+    assert!(sample.iter().all(|anagram| anagram != "ordeals"));
+
+    assert_eq!(Sampler::new("abcd").sample_many(13, &mut rng).len(), 13);
+    assert_eq!(
+      Sampler::new("abc").sample_many(usize::MAX, &mut rng).len(),
+      6
+    );
+    assert_eq!(
+      Sampler::new("abcdefg").sample_many(2000, &mut rng).len(),
+      2000
+    );
+    assert!(Sampler::new("a")
+      .exclude_identity(true)
+      .sample_many(1, &mut rng)
+      .is_empty());
+  }
+}
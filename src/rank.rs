@@ -0,0 +1,252 @@
+use crate::{count_arrangements_big, AnagramOptions, Error};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use std::collections::BTreeMap;
+
+/// Get the number of times each distinct unit occurs in a word, in sorted
+/// order
+fn sorted_multiplicities<'a>(units: &[&'a str]) -> BTreeMap<&'a str, usize> {
+  let mut counts = BTreeMap::new();
+  for &unit in units {
+    *counts.entry(unit).or_insert(0) += 1;
+  }
+  counts
+}
+
+/// Get the position of a word among all of its anagrams in lexicographic
+/// order, starting from zero for the sorted form
+///
+/// Panics if the rank does not fit in a `u128`, see `rank_big` for long words
+/// Examples:
+/// "abc" -> 0
+/// "cba" -> 5
+/// "baa" -> 2
+pub fn rank(word: &str) -> u128 {
+  try_rank(word).expect("anagram rank overflowed u128, use `rank_big` instead")
+}
+
+/// Get the position of a word among all of its anagrams in lexicographic
+/// order, returning `None` if the rank does not fit in a `u128`
+pub fn try_rank(word: &str) -> Option<u128> {
+  rank_big(word).to_u128()
+}
+
+/// Get the lexicographic position of a word among its anagrams after
+/// normalizing it with the given options
+pub fn rank_with(word: &str, options: &AnagramOptions) -> u128 {
+  try_rank_with(word, options).expect("anagram rank overflowed u128, use `rank_big` instead")
+}
+
+/// Get the lexicographic position of a word among its anagrams after
+/// normalizing it with the given options, returning `None` on overflow
+pub fn try_rank_with(word: &str, options: &AnagramOptions) -> Option<u128> {
+  rank_big_with(word, options).to_u128()
+}
+
+/// Get the position of a word among all of its anagrams in lexicographic
+/// order, exactly, no matter how long the word is
+pub fn rank_big(word: &str) -> BigUint {
+  rank_units(&AnagramOptions::new().units(word))
+}
+
+/// Get the lexicographic position of a word among its anagrams after
+/// normalizing it with the given options, exactly
+pub fn rank_big_with(word: &str, options: &AnagramOptions) -> BigUint {
+  rank_units(&options.units(&options.normalize(word)))
+}
+
+/// Get the position of a sequence of units among all of their orderings in
+/// lexicographic order
+fn rank_units(units: &[&str]) -> BigUint {
+  let mut counts = sorted_multiplicities(units);
+  let mut remaining = units.len();
+
+  // number of anagrams of the letters not yet placed
+  let mut total = count_arrangements_big(units);
+  let mut rank = BigUint::zero();
+
+  for &unit in units {
+    // skip over every anagram that starts with a smaller letter here
+    for (_, &k) in counts.range(..unit) {
+      rank += &total * k / remaining;
+    }
+
+    let k = counts.get_mut(unit).unwrap();
+    total = total * *k / remaining;
+    *k -= 1;
+    remaining -= 1;
+  }
+
+  rank
+}
+
+/// Get the anagram of `letters` at position `n` in lexicographic order, the
+/// inverse of `rank`
+///
+/// Panics if `n` is not less than `count(letters)`
+/// Examples:
+/// ("cba", 0) -> "abc"
+/// ("cba", 5) -> "cba"
+/// ("aab", 2) -> "baa"
+pub fn unrank(letters: &str, n: u128) -> String {
+  unrank_big(letters, &BigUint::from(n))
+}
+
+/// Get the anagram at position `n` of `letters` after normalizing them with
+/// the given options
+pub fn unrank_with(letters: &str, n: u128, options: &AnagramOptions) -> String {
+  unrank_big_with(letters, &BigUint::from(n), options)
+}
+
+/// Get the anagram of `letters` at position `n` in lexicographic order, for
+/// positions that do not fit in a `u128`
+///
+/// Panics if `n` is not less than `count_big(letters)`
+pub fn unrank_big(letters: &str, n: &BigUint) -> String {
+  try_unrank_big(letters, n).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Get the anagram of `letters` at position `n` in lexicographic order, or an
+/// error if there are not that many anagrams
+pub(crate) fn try_unrank_big(letters: &str, n: &BigUint) -> Result<String, Error> {
+  try_unrank_units(&AnagramOptions::new().units(letters), n)
+}
+
+/// Get the anagram at position `n` of `letters` after normalizing them with
+/// the given options, or an error if there are not that many anagrams
+pub(crate) fn try_unrank_big_with(
+  letters: &str,
+  n: &BigUint,
+  options: &AnagramOptions,
+) -> Result<String, Error> {
+  try_unrank_units(&options.units(&options.normalize(letters)), n)
+}
+
+/// Get the ordering of a sequence of units at position `n` in lexicographic
+/// order, or an error if there are not that many orderings
+fn try_unrank_units(units: &[&str], n: &BigUint) -> Result<String, Error> {
+  let mut counts = sorted_multiplicities(units);
+  let mut remaining = units.len();
+
+  let mut total = count_arrangements_big(units);
+  let mut n = n.clone();
+
+  if n >= total {
+    return Err(Error::RankOutOfRange {
+      rank:  n,
+      count: total,
+    });
+  }
+
+  let mut word = String::with_capacity(units.iter().map(|unit| unit.len()).sum());
+
+  while remaining > 0 {
+    for (&unit, k) in counts.iter_mut().filter(|(_, k)| **k > 0) {
+      // number of anagrams that place `unit` here
+      let block = &total * *k / remaining;
+
+      if n < block {
+        word.push_str(unit);
+        total = block;
+        *k -= 1;
+        break;
+      }
+
+      n -= block;
+    }
+
+    remaining -= 1;
+  }
+
+  Ok(word)
+}
+
+/// Get the anagram at position `n` of `letters` after normalizing them with
+/// the given options, for positions that do not fit in a `u128`
+pub fn unrank_big_with(letters: &str, n: &BigUint, options: &AnagramOptions) -> String {
+  try_unrank_big_with(letters, n, options).unwrap_or_else(|error| panic!("{}", error))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{anagrams, count_big};
+
+  #[test]
+  fn test_rank() {
+    assert_eq!(rank("abc"), 0);
+    assert_eq!(rank("acb"), 1);
+    assert_eq!(rank("cba"), 5);
+    assert_eq!(rank("baa"), 2);
+    assert_eq!(rank(""), 0);
+    assert_eq!(rank("mississippi"), 13736);
+
+    for (i, word) in anagrams("ordeal").enumerate() {
+      assert_eq!(rank(&word), i as u128);
+    }
+  }
+
+  #[test]
+  fn test_rank_big() {
+    let word = "zyxwvutsrqponmlkjihgfedcbaIHGFEDCBA";
+    assert_eq!(rank_big(word), count_big(word) - 1u32);
+    assert_eq!(
+      rank_big("ABCDEFGHIabcdefghijklmnopqrstuvwxzy"),
+      BigUint::from(1u32)
+    );
+  }
+
+  #[test]
+  fn test_unrank() {
+    assert_eq!(unrank("cba", 0), "abc");
+    assert_eq!(unrank("cba", 5), "cba");
+    assert_eq!(unrank("aab", 2), "baa");
+    assert_eq!(unrank("", 0), "");
+    assert_eq!(unrank("mississippi", 13736), "mississippi");
+
+    for (i, word) in anagrams("bookkeeper").enumerate().step_by(97) {
+      assert_eq!(unrank("bookkeeper", i as u128), word);
+    }
+  }
+
+  #[test]
+  fn test_rank_with() {
+    let options = AnagramOptions::phrase();
+    assert_eq!(rank_with("C, b a", &options), 5);
+    assert_eq!(try_rank_with("C, b a", &options), Some(5));
+    assert_eq!(rank_big_with("C, b a", &options), BigUint::from(5u32));
+    assert_eq!(unrank_with("C, b a", 1, &options), "acb");
+    assert_eq!(
+      unrank_big_with("C, b a", &BigUint::from(1u32), &options),
+      "acb"
+    );
+  }
+
+  #[test]
+  fn test_rank_with_graphemes() {
+    let options = AnagramOptions::new().graphemes(true);
+    assert_eq!(rank_with("e\u{301}a", &options), 1);
+    assert_eq!(unrank_with("e\u{301}a", 1, &options), "e\u{301}a");
+    assert_eq!(unrank_with("e\u{301}a", 0, &options), "ae\u{301}");
+
+    for (i, word) in crate::anagrams_with("ñe\u{301}ñ", &options).enumerate() {
+      assert_eq!(rank_with(&word, &options), i as u128);
+      assert_eq!(unrank_with("ñe\u{301}ñ", i as u128, &options), word);
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "anagram rank out of range")]
+  fn test_unrank_out_of_range() {
+    unrank("aab", 3);
+  }
+
+  #[test]
+  fn test_unrank_big() {
+    let letters = "abcdefghijklmnopqrstuvwxyzABCDEFGHI";
+    let last = count_big(letters) - 1u32;
+    let word = unrank_big(letters, &last);
+    assert_eq!(word, "zyxwvutsrqponmlkjihgfedcbaIHGFEDCBA");
+    assert_eq!(rank_big(&word), last);
+  }
+}
@@ -0,0 +1,428 @@
+use crate::{
+  options::{Span, UnitCursor},
+  AnagramOptions,
+};
+use std::{
+  collections::{HashMap, VecDeque},
+  iter::FusedIterator,
+  ops::Range,
+};
+
+/// Find every window of a text that is an anagram of a pattern
+///
+/// Examples:
+/// ("forxxorfxdofr", "for") -> "for" at 0..3, "orf" at 5..8, "ofr" at 10..13
+pub fn find_anagrams<'a>(text: &'a str, pattern: &str) -> FindAnagrams<'a> {
+  find_anagrams_with(text, pattern, &AnagramOptions::new())
+}
+
+/// Find every window of a text that is an anagram of a pattern after
+/// normalizing both with the given options
+pub fn find_anagrams_with<'a>(
+  text: &'a str,
+  pattern: &str,
+  options: &AnagramOptions,
+) -> FindAnagrams<'a> {
+  find_approximate_anagrams_with(text, pattern, 0, options)
+}
+
+/// Find every window of a text that is an anagram of a pattern with up to
+/// `mismatches` letters substituted
+///
+/// Examples:
+/// ("forxxorfxdofr", "for", 1) -> "for", "orx", "xor", "orf", "rfx", "dof",
+/// "ofr"
+pub fn find_approximate_anagrams<'a>(
+  text: &'a str,
+  pattern: &str,
+  mismatches: usize,
+) -> FindAnagrams<'a> {
+  find_approximate_anagrams_with(text, pattern, mismatches, &AnagramOptions::new())
+}
+
+/// Find every window of a text that is an anagram of a pattern with up to
+/// `mismatches` letters substituted, after normalizing both with the given
+/// options
+pub fn find_approximate_anagrams_with<'a>(
+  text: &'a str,
+  pattern: &str,
+  mismatches: usize,
+  options: &AnagramOptions,
+) -> FindAnagrams<'a> {
+  FindAnagrams {
+    matches: Matches::new(text, pattern, mismatches, options),
+    text,
+  }
+}
+
+/// An occurence of an anagram of a pattern in a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<'a> {
+  /// The byte offsets of the occurence in the text
+  pub bytes:      Range<usize>,
+  /// The char offsets of the occurence in the text
+  pub chars:      Range<usize>,
+  /// The matched substring, including any chars the options ignored
+  pub text:       &'a str,
+  /// The number of letters that differ from an anagram of the pattern
+  pub mismatches: usize,
+}
+
+/// An iterator over the occurences of anagrams of a pattern in a text,
+/// created by [`find_anagrams`]
+#[derive(Debug, Clone)]
+pub struct FindAnagrams<'a> {
+  text:    &'a str,
+  matches: Matches<'a>,
+}
+
+impl<'a> Iterator for FindAnagrams<'a> {
+  type Item = Match<'a>;
+
+  fn next(&mut self) -> Option<Match<'a>> {
+    self.matches.next().map(|(span, mismatches)| Match {
+      text: &self.text[span.bytes.clone()],
+      bytes: span.bytes,
+      chars: span.chars,
+      mismatches,
+    })
+  }
+}
+
+impl FusedIterator for FindAnagrams<'_> {}
+
+/// Assigns a dense id to every distinct unit of a set of patterns, with every
+/// unit that appears in none of them sharing id zero
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Symbols {
+  ids: HashMap<String, usize>,
+}
+
+impl Symbols {
+  /// The id shared by every unit that is not part of a pattern
+  pub(crate) const OTHER: usize = 0;
+
+  pub(crate) fn new() -> Self {
+    Self::default()
+  }
+
+  /// Get the id of a pattern unit, assigning a new one if needed
+  pub(crate) fn intern(&mut self, unit: &str) -> usize {
+    let next = self.ids.len() + 1;
+    *self.ids.entry(unit.to_owned()).or_insert(next)
+  }
+
+  /// Get the id of a text unit
+  pub(crate) fn get(&self, unit: &str) -> usize {
+    self.ids.get(unit).copied().unwrap_or(Self::OTHER)
+  }
+
+  /// The number of ids in use, including `OTHER`
+  pub(crate) fn len(&self) -> usize {
+    self.ids.len() + 1
+  }
+
+  /// Normalize a pattern with the given options and intern its units
+  pub(crate) fn intern_pattern(&mut self, pattern: &str, options: &AnagramOptions) -> Vec<usize> {
+    let mut ids = Vec::new();
+    options.for_each_unit(pattern, |unit, _| ids.push(self.intern(unit)));
+    ids
+  }
+}
+
+/// The difference between the histogram of a sliding window of text and the
+/// histogram of a pattern, along with its L1 norm
+#[derive(Debug, Clone)]
+pub(crate) struct Window {
+  counts:   Vec<i64>,
+  distance: usize,
+}
+
+impl Window {
+  /// Create an empty window for a pattern, whose distance is the pattern
+  /// length
+  pub(crate) fn new(pattern: &[usize], symbols: usize) -> Self {
+    let mut counts = vec![0; symbols];
+
+    for &symbol in pattern {
+      counts[symbol] -= 1;
+    }
+
+    Self {
+      counts,
+      distance: pattern.len(),
+    }
+  }
+
+  /// Add a unit entering the window
+  pub(crate) fn push(&mut self, symbol: usize) {
+    let count = &mut self.counts[symbol];
+    // moving towards the pattern count shrinks the distance
+    if *count < 0 {
+      self.distance -= 1;
+    } else {
+      self.distance += 1;
+    }
+    *count += 1;
+  }
+
+  /// Remove a unit leaving the window
+  pub(crate) fn pop(&mut self, symbol: usize) {
+    let count = &mut self.counts[symbol];
+    if *count > 0 {
+      self.distance -= 1;
+    } else {
+      self.distance += 1;
+    }
+    *count -= 1;
+  }
+
+  /// The number of units that would need to be added or removed to turn the
+  /// window into an anagram of the pattern
+  pub(crate) fn distance(&self) -> usize {
+    self.distance
+  }
+}
+
+/// A window sliding over the units of a text that remembers where the last
+/// few came from, and only reports windows that start and end on the edges
+/// of raw units
+#[derive(Debug, Clone)]
+pub(crate) struct Slider<S> {
+  window: Window,
+  // each unit along with where its raw unit came from and whether it is the
+  // first unit that raw unit normalized to
+  recent: VecDeque<(usize, S, bool)>,
+  len:    usize,
+}
+
+impl<S: Clone> Slider<S> {
+  pub(crate) fn new(pattern: &[usize], symbols: usize) -> Self {
+    Self {
+      window: Window::new(pattern, symbols),
+      recent: VecDeque::with_capacity(pattern.len() + 1),
+      len:    pattern.len(),
+    }
+  }
+
+  /// Check if the pattern is empty, in which case nothing ever matches
+  pub(crate) fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Slide the window over the units one raw unit normalized to, returning
+  /// where the window starts and its distance from the pattern if it is full
+  /// and does not cut a raw unit apart
+  pub(crate) fn push(&mut self, symbols: &[usize], span: &S) -> Option<(&S, usize)> {
+    for (i, &symbol) in symbols.iter().enumerate() {
+      // add last unit
+      self.window.push(symbol);
+      self.recent.push_back((symbol, span.clone(), i == 0));
+
+      // remove first unit
+      if self.recent.len() > self.len {
+        let (first, _, _) = self.recent.pop_front().unwrap();
+        self.window.pop(first);
+      }
+    }
+
+    // a raw unit that normalized to nothing leaves the window as it was
+    if symbols.is_empty() || self.is_empty() || self.recent.len() < self.len {
+      return None;
+    }
+
+    match &self.recent[0] {
+      (_, first, true) => Some((first, self.window.distance())),
+      _ => None,
+    }
+  }
+}
+
+/// An iterator over the spans of every window of a text that is an anagram
+/// of a pattern with up to a given number of substitutions, along with the
+/// number of substitutions each one needs
+#[derive(Debug, Clone)]
+pub(crate) struct Matches<'a> {
+  units:      UnitCursor<'a>,
+  options:    AnagramOptions,
+  symbols:    Symbols,
+  slider:     Slider<Span>,
+  batch:      Vec<usize>,
+  mismatches: usize,
+}
+
+impl<'a> Matches<'a> {
+  pub(crate) fn new(
+    text: &'a str,
+    pattern: &str,
+    mismatches: usize,
+    options: &AnagramOptions,
+  ) -> Self {
+    let mut symbols = Symbols::new();
+    let pattern = symbols.intern_pattern(pattern, options);
+
+    Self {
+      units: UnitCursor::new(text),
+      options: options.clone(),
+      slider: Slider::new(&pattern, symbols.len()),
+      batch: Vec::new(),
+      symbols,
+      mismatches,
+    }
+  }
+}
+
+impl FusedIterator for Matches<'_> {}
+
+impl Iterator for Matches<'_> {
+  type Item = (Span, usize);
+
+  fn next(&mut self) -> Option<(Span, usize)> {
+    // an empty pattern never matches
+    if self.slider.is_empty() {
+      return None;
+    }
+
+    loop {
+      let (symbols, batch) = (&self.symbols, &mut self.batch);
+      batch.clear();
+
+      let last = self
+        .units
+        .advance(&self.options, |unit, _| batch.push(symbols.get(unit)))?;
+
+      // each substitution adds one unit and removes another, moving the
+      // window two units away from the pattern
+      match self.slider.push(&self.batch, &last) {
+        Some((first, distance)) if distance <= 2 * self.mismatches => {
+          let span = Span {
+            bytes: first.bytes.start..last.bytes.end,
+            chars: first.chars.start..last.chars.end,
+          };
+
+          return Some((span, distance / 2));
+        },
+        _ => {},
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Case;
+
+  #[test]
+  fn test_window() {
+    let mut window = Window::new(&[1, 1, 2], 3);
+    assert_eq!(window.distance(), 3);
+    window.push(1);
+    window.push(0);
+    assert_eq!(window.distance(), 3);
+    window.push(2);
+    window.pop(1);
+    assert_eq!(window.distance(), 3);
+    window.pop(0);
+    window.push(1);
+    window.push(1);
+    assert_eq!(window.distance(), 0);
+  }
+
+  #[test]
+  fn test_find_anagrams() {
+    let matches: Vec<_> = find_anagrams("forxxorfxdofr", "for").collect();
+    assert_eq!(matches, [
+      Match {
+        bytes:      0..3,
+        chars:      0..3,
+        text:       "for",
+        mismatches: 0,
+      },
+      Match {
+        bytes:      5..8,
+        chars:      5..8,
+        text:       "orf",
+        mismatches: 0,
+      },
+      Match {
+        bytes:      10..13,
+        chars:      10..13,
+        text:       "ofr",
+        mismatches: 0,
+      },
+    ]);
+    assert_eq!(find_anagrams("abc", "abcd").count(), 0);
+  }
+
+  #[test]
+  fn test_find_anagrams_with() {
+    let matches: Vec<_> = find_anagrams_with(
+      "Él, dormía: Dirty Room!",
+      "dormitory",
+      &AnagramOptions::phrase(),
+    )
+    .map(|m| (m.bytes, m.chars, m.text))
+    .collect();
+    assert_eq!(matches, [(14..24, 12..22, "Dirty Room")]);
+
+    let matches: Vec<_> =
+      find_anagrams_with("xñáy", "añ", &AnagramOptions::new().strip_diacritics(true))
+        .map(|m| (m.bytes, m.chars, m.text))
+        .collect();
+    assert_eq!(matches, [(1..5, 1..3, "ñá")]);
+
+    // a window may not start or end inside a char that folds to several
+    let fold = AnagramOptions::new().case(Case::Fold);
+    assert_eq!(find_anagrams_with("aß", "as", &fold).count(), 0);
+    assert_eq!(find_anagrams_with("ßa", "sa", &fold).count(), 0);
+    assert_eq!(
+      find_anagrams_with("aßa", "ssa", &fold)
+        .map(|m| m.text)
+        .collect::<Vec<_>>(),
+      ["aß", "ßa"]
+    );
+    assert!(!crate::is_anagram_with("aß", "as", &fold));
+  }
+
+  #[test]
+  fn test_find_approximate_anagrams() {
+    let matches: Vec<_> = find_approximate_anagrams("forxxorfxdofr", "for", 1)
+      .map(|m| (m.text, m.mismatches))
+      .collect();
+    assert_eq!(matches, [
+      ("for", 0),
+      ("orx", 1),
+      ("xor", 1),
+      ("orf", 0),
+      ("rfx", 1),
+      ("dof", 1),
+      ("ofr", 0),
+    ]);
+    assert_eq!(find_approximate_anagrams("abcdef", "xyz", 3).count(), 4);
+    assert_eq!(find_approximate_anagrams("abcdef", "xyz", 2).count(), 0);
+    assert_eq!(find_approximate_anagrams("ab", "abc", 3).count(), 0);
+  }
+
+  #[test]
+  fn test_find_approximate_anagrams_with() {
+    let matches: Vec<_> =
+      find_approximate_anagrams_with("Dirty Rôom", "dormitory", 1, &AnagramOptions::phrase())
+        .map(|m| (m.text, m.mismatches))
+        .collect();
+    assert_eq!(matches, [("Dirty Rôom", 1)]);
+  }
+
+  #[test]
+  fn test_matches() {
+    let spans: Vec<_> = Matches::new("añaña", "ña", 0, &AnagramOptions::new())
+      .map(|(span, _)| (span.bytes, span.chars))
+      .collect();
+    assert_eq!(spans, [
+      (0..3, 0..2),
+      (1..4, 1..3),
+      (3..6, 2..4),
+      (4..7, 3..5)
+    ]);
+  }
+}
@@ -0,0 +1,380 @@
+use crate::{
+  options::UnitCursor,
+  search::{Slider, Symbols},
+  AnagramOptions,
+};
+use std::{
+  collections::VecDeque,
+  io::{self, ErrorKind, Read},
+  iter::FusedIterator,
+  ops::Range,
+  str,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How many bytes are read from the underlying reader at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Find every window of a stream of UTF-8 text that is an anagram of a
+/// pattern, reading it in chunks so memory use stays bounded no matter how
+/// large the stream is
+pub fn find_anagrams_in_reader<R: Read>(reader: R, pattern: &str) -> ReaderMatches<R> {
+  find_anagrams_in_reader_with(reader, pattern, &AnagramOptions::new())
+}
+
+/// Find every window of a stream of UTF-8 text that is an anagram of a
+/// pattern after normalizing both with the given options
+pub fn find_anagrams_in_reader_with<R: Read>(
+  reader: R,
+  pattern: &str,
+  options: &AnagramOptions,
+) -> ReaderMatches<R> {
+  let mut symbols = Symbols::new();
+  let pattern = symbols.intern_pattern(pattern, options);
+
+  ReaderMatches {
+    slider: Slider::new(&pattern, symbols.len()),
+    options: options.clone(),
+    batch: Vec::new(),
+    pending: VecDeque::new(),
+    buffer: Vec::new(),
+    filled: 0,
+    bytes: 0,
+    chars: 0,
+    done: false,
+    error: None,
+    symbols,
+    reader,
+  }
+}
+
+/// Count the number of occurences of an anagram in a stream of UTF-8 text
+pub fn occurences_in_reader<R: Read>(reader: R, pattern: &str) -> io::Result<u128> {
+  occurences_in_reader_with(reader, pattern, &AnagramOptions::new())
+}
+
+/// Count the number of occurences of an anagram in a stream of UTF-8 text
+/// after normalizing both with the given options
+pub fn occurences_in_reader_with<R: Read>(
+  reader: R,
+  pattern: &str,
+  options: &AnagramOptions,
+) -> io::Result<u128> {
+  let mut count = 0;
+
+  for found in find_anagrams_in_reader_with(reader, pattern, options) {
+    found?;
+    count += 1;
+  }
+
+  Ok(count)
+}
+
+/// An occurence of an anagram of a pattern in a stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamMatch {
+  /// The byte offsets of the occurence from the start of the stream
+  pub bytes: Range<u64>,
+  /// The char offsets of the occurence from the start of the stream
+  pub chars: Range<u64>,
+}
+
+/// An iterator over the occurences of anagrams of a pattern in a stream,
+/// created by [`find_anagrams_in_reader`]
+///
+/// Only the current chunk and the units in the window are kept in memory,
+/// along with at most one chunk of a grapheme cluster that may continue in
+/// the next one.
+/// Reading stops at the first error, which is yielded once after every match
+/// before it
+#[derive(Debug)]
+pub struct ReaderMatches<R> {
+  reader:  R,
+  options: AnagramOptions,
+  symbols: Symbols,
+  slider:  Slider<(Range<u64>, Range<u64>)>,
+  batch:   Vec<usize>,
+  pending: VecDeque<StreamMatch>,
+  buffer:  Vec<u8>,
+  filled:  usize,
+  bytes:   u64,
+  chars:   u64,
+  done:    bool,
+  error:   Option<io::Error>,
+}
+
+impl<R: Read> ReaderMatches<R> {
+  /// Read the next chunk and slide the window over every complete unit in
+  /// it, returning `false` at the end of the stream
+  fn fill(&mut self) -> io::Result<bool> {
+    // the buffer only grows, and so only gets zeroed, when the text held
+    // back leaves less than a chunk of room
+    if self.buffer.len() - self.filled < CHUNK_SIZE {
+      self.buffer.resize(self.filled + CHUNK_SIZE, 0);
+    }
+
+    let read = loop {
+      match self.reader.read(&mut self.buffer[self.filled..]) {
+        Ok(read) => break read,
+        Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+        Err(error) => return Err(error),
+      }
+    };
+
+    self.filled += read;
+    let eof = read == 0;
+
+    let (valid, error) = match str::from_utf8(&self.buffer[..self.filled]) {
+      Ok(text) => (text.len(), None),
+      // a char cut off at the end of the chunk is completed by the next one
+      Err(error) if error.error_len().is_none() && !eof => (error.valid_up_to(), None),
+      Err(error) => (error.valid_up_to(), Some(error)),
+    };
+
+    let text = str::from_utf8(&self.buffer[..valid]).unwrap();
+
+    // the last grapheme cluster, or the marks composing with its last char,
+    // may continue in the next chunk, unless it is already longer than a
+    // chunk, in which case it is split rather than letting the buffer grow
+    // without bound
+    let joins = self.options.uses_graphemes() || self.options.composes();
+
+    let end = if joins && error.is_none() && !eof {
+      let last = text
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start);
+
+      if text.len() - last > CHUNK_SIZE {
+        text.len()
+      } else {
+        last
+      }
+    } else {
+      text.len()
+    };
+
+    let text = &text[..end];
+    let (bytes, chars) = (self.bytes, self.chars);
+    let mut units = UnitCursor::new(text);
+
+    loop {
+      let (symbols, batch) = (&self.symbols, &mut self.batch);
+      batch.clear();
+
+      let span = match units.advance(&self.options, |unit, _| batch.push(symbols.get(unit))) {
+        Some(span) => span,
+        None => break,
+      };
+
+      let last = (
+        bytes + span.bytes.start as u64..bytes + span.bytes.end as u64,
+        chars + span.chars.start as u64..chars + span.chars.end as u64,
+      );
+
+      if let Some(((first_bytes, first_chars), 0)) = self.slider.push(&self.batch, &last) {
+        self.pending.push_back(StreamMatch {
+          bytes: first_bytes.start..last.0.end,
+          chars: first_chars.start..last.1.end,
+        });
+      }
+    }
+
+    self.bytes += end as u64;
+    self.chars += text.chars().count() as u64;
+    self.buffer.copy_within(end..self.filled, 0);
+    self.filled -= end;
+
+    match error {
+      Some(error) => Err(io::Error::new(ErrorKind::InvalidData, error)),
+      None => Ok(!eof),
+    }
+  }
+}
+
+impl<R: Read> Iterator for ReaderMatches<R> {
+  type Item = io::Result<StreamMatch>;
+
+  fn next(&mut self) -> Option<io::Result<StreamMatch>> {
+    // an empty pattern never matches
+    while self.pending.is_empty() && !self.done && !self.slider.is_empty() {
+      match self.fill() {
+        Ok(more) => self.done = !more,
+        Err(error) => {
+          self.done = true;
+          self.error = Some(error);
+        },
+      }
+    }
+
+    match self.pending.pop_front() {
+      Some(found) => Some(Ok(found)),
+      None => self.error.take().map(Err),
+    }
+  }
+}
+
+impl<R: Read> FusedIterator for ReaderMatches<R> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{find_anagrams, find_anagrams_with, Normalization};
+
+  /// A reader that hands out a few bytes at a time, splitting chars
+  struct Trickle<'a> {
+    bytes: &'a [u8],
+    step:  usize,
+  }
+
+  impl Read for Trickle<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+      let n = self.step.min(self.bytes.len()).min(buffer.len());
+      buffer[..n].copy_from_slice(&self.bytes[..n]);
+      self.bytes = &self.bytes[n..];
+      Ok(n)
+    }
+  }
+
+  fn offsets(matches: ReaderMatches<impl Read>) -> Vec<(Range<u64>, Range<u64>)> {
+    matches
+      .map(|found| found.map(|found| (found.bytes, found.chars)))
+      .collect::<io::Result<_>>()
+      .unwrap()
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader() {
+    let text = "forxxorfxdofr ñañaña 🦀🎉🦀";
+
+    for &pattern in &["for", "añ", "🦀🎉", "x"] {
+      let expected: Vec<_> = find_anagrams(text, pattern)
+        .map(|found| {
+          (
+            found.bytes.start as u64..found.bytes.end as u64,
+            found.chars.start as u64..found.chars.end as u64,
+          )
+        })
+        .collect();
+
+      for step in 1..5 {
+        let reader = Trickle {
+          bytes: text.as_bytes(),
+          step,
+        };
+        assert_eq!(offsets(find_anagrams_in_reader(reader, pattern)), expected);
+      }
+
+      assert_eq!(
+        offsets(find_anagrams_in_reader(text.as_bytes(), pattern)),
+        expected
+      );
+    }
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader_with() {
+    let text = "Dirty room, ae\u{301} e\u{301}a";
+    let options = AnagramOptions::phrase().graphemes(true);
+
+    for &pattern in &["dormitory", "e\u{301}a"] {
+      let expected: Vec<_> = find_anagrams_with(text, pattern, &options)
+        .map(|found| found.bytes.start as u64..found.bytes.end as u64)
+        .collect();
+
+      let reader = Trickle {
+        bytes: text.as_bytes(),
+        step:  1,
+      };
+
+      let found: Vec<_> = find_anagrams_in_reader_with(reader, pattern, &options)
+        .map(|found| found.unwrap().bytes)
+        .collect();
+
+      assert_eq!(found, expected);
+      assert!(!found.is_empty());
+    }
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader_composed() {
+    // a mark at the start of the next chunk still composes with the char
+    // before it
+    let text = "xe\u{301}ye\u{301}";
+    let options = AnagramOptions::new().normalization(Normalization::Nfc);
+
+    let reader = Trickle {
+      bytes: text.as_bytes(),
+      step:  1,
+    };
+
+    let found: Vec<_> = find_anagrams_in_reader_with(reader, "\u{e9}y", &options)
+      .map(|found| found.unwrap().chars)
+      .collect();
+
+    assert_eq!(found, [1..4, 3..6]);
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader_long_grapheme() {
+    // one grapheme cluster that never ends still only holds back a chunk
+    let text = format!("ab{}", "\u{301}".repeat(2 * CHUNK_SIZE));
+    let options = AnagramOptions::new().graphemes(true);
+    let mut matches = find_anagrams_in_reader_with(text.as_bytes(), "ab", &options);
+
+    while matches.fill().unwrap() {
+      assert!(matches.filled <= CHUNK_SIZE);
+      assert!(matches.buffer.len() <= 2 * CHUNK_SIZE);
+    }
+
+    let text = format!("{}ab ba", "\u{200d}".repeat(3 * CHUNK_SIZE));
+    assert_eq!(
+      occurences_in_reader_with(text.as_bytes(), "ab", &options).unwrap(),
+      2
+    );
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader_reuses_buffer() {
+    let text = "ab".repeat(CHUNK_SIZE);
+    let mut matches = find_anagrams_in_reader(text.as_bytes(), "ba");
+
+    while matches.fill().unwrap() {
+      assert_eq!(matches.buffer.len(), CHUNK_SIZE);
+    }
+
+    assert_eq!(matches.pending.len(), 2 * CHUNK_SIZE - 1);
+  }
+
+  #[test]
+  fn test_find_anagrams_in_reader_invalid() {
+    let mut matches = find_anagrams_in_reader(&b"ab\xffba"[..], "ab");
+    assert_eq!(matches.next().unwrap().unwrap().bytes, 0..2);
+    assert_eq!(
+      matches.next().unwrap().unwrap_err().kind(),
+      ErrorKind::InvalidData
+    );
+    assert!(matches.next().is_none());
+
+    let mut matches = find_anagrams_in_reader(&b"ab\xc3"[..], "ab");
+    assert!(matches.next().unwrap().is_ok());
+    assert_eq!(
+      matches.next().unwrap().unwrap_err().kind(),
+      ErrorKind::InvalidData
+    );
+  }
+
+  #[test]
+  fn test_occurences_in_reader() {
+    assert_eq!(
+      occurences_in_reader(&b"hellohelloleh"[..], "hel").unwrap(),
+      3
+    );
+    assert_eq!(occurences_in_reader(&b"ab"[..], "abc").unwrap(), 0);
+    assert_eq!(occurences_in_reader(&b"ab"[..], "").unwrap(), 0);
+    assert_eq!(
+      occurences_in_reader_with(&b"Ab, BA"[..], "ab", &AnagramOptions::phrase()).unwrap(),
+      2
+    );
+  }
+}
@@ -0,0 +1,329 @@
+use crate::{multinomial_big, AnagramOptions, Case};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use std::collections::{BTreeMap, HashMap};
+
+/// A blank tile, a char that stands in for any one letter of an alphabet
+///
+/// Examples:
+/// Wildcard::new('?') matches any of "a" to "z"
+/// Wildcard::new('_').alphabet("acgt") matches any of "a", "c", "g" or "t"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wildcard {
+  symbol:   char,
+  alphabet: Vec<char>,
+}
+
+impl Wildcard {
+  /// Create a wildcard that matches any lowercase ASCII letter
+  pub fn new(symbol: char) -> Self {
+    Self {
+      symbol,
+      alphabet: ('a'..='z').collect(),
+    }
+  }
+
+  /// Set the letters the wildcard can stand in for
+  pub fn alphabet<I: IntoIterator<Item = char>>(mut self, alphabet: I) -> Self {
+    self.alphabet = alphabet.into_iter().collect();
+    self.alphabet.sort_unstable();
+    self.alphabet.dedup();
+    self
+  }
+
+  /// The char that marks a wildcard
+  pub fn symbol(&self) -> char {
+    self.symbol
+  }
+
+  /// Check if the wildcard can stand in for a unit
+  pub(crate) fn matches(&self, unit: &str) -> Option<char> {
+    let mut chars = unit.chars();
+
+    match (chars.next(), chars.next()) {
+      (Some(c), None) if self.alphabet.binary_search(&c).is_ok() => Some(c),
+      _ => None,
+    }
+  }
+
+  /// Split text into the number of wildcards in it and everything else
+  pub(crate) fn split(&self, text: &str) -> (usize, String) {
+    let blanks = text.chars().filter(|&c| c == self.symbol).count();
+    let rest = text.chars().filter(|&c| c != self.symbol).collect();
+    (blanks, rest)
+  }
+}
+
+/// A dictionary word matched by a query with wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WildcardMatch<'a> {
+  /// The matched word
+  pub word:        &'a str,
+  /// The letter each wildcard stands for, in sorted order
+  pub assignments: Vec<char>,
+}
+
+/// Check if a word is an anagram of a pattern containing wildcards, ignoring
+/// case, returning the letters the wildcards stand for in sorted order
+///
+/// Examples:
+/// ("re?t", "tree") -> Some(['e'])
+/// ("re?t", "rent") -> Some(['n'])
+/// ("re?t", "rest!") -> None
+pub fn is_anagram_wildcard(pattern: &str, word: &str, wildcard: &Wildcard) -> Option<Vec<char>> {
+  is_anagram_wildcard_with(
+    pattern,
+    word,
+    wildcard,
+    &AnagramOptions::new().case(Case::Lower),
+  )
+}
+
+/// Check if a word is an anagram of a pattern containing wildcards after
+/// normalizing both with the given options, returning the letters the
+/// wildcards stand for in sorted order
+///
+/// Wildcards are taken out of the pattern before it is normalized, so they
+/// work even when the options would ignore the wildcard char
+pub fn is_anagram_wildcard_with(
+  pattern: &str,
+  word: &str,
+  wildcard: &Wildcard,
+  options: &AnagramOptions,
+) -> Option<Vec<char>> {
+  let (blanks, pattern) = wildcard.split(pattern);
+
+  let word = options.normalize(word);
+  let mut count: BTreeMap<&str, usize> = BTreeMap::new();
+
+  for unit in options.units(&word) {
+    *count.entry(unit).or_insert(0) += 1;
+  }
+
+  let pattern = options.normalize(&pattern);
+
+  for unit in options.units(&pattern) {
+    match count.get_mut(unit) {
+      Some(n) if *n > 0 => *n -= 1,
+      _ => return None,
+    }
+  }
+
+  // whatever the pattern's letters didn't cover must be covered by blanks
+  let mut assignments = Vec::with_capacity(blanks);
+
+  for (unit, n) in count {
+    let c = if n > 0 {
+      wildcard.matches(unit)?
+    } else {
+      continue;
+    };
+    assignments.resize(assignments.len() + n, c);
+  }
+
+  if assignments.len() == blanks {
+    Some(assignments)
+  } else {
+    None
+  }
+}
+
+/// Count the number of distinct strings that can be formed from a word whose
+/// wildcards may each be any letter of the wildcard's alphabet, ignoring case
+///
+/// Panics if the count does not fit in a `u128`, see `try_count_wildcard` and
+/// `count_wildcard_big` for long words
+/// Examples:
+/// ("a?", Wildcard::new('?').alphabet("ab")) -> 3, "aa", "ab" and "ba"
+pub fn count_wildcard(word: &str, wildcard: &Wildcard) -> u128 {
+  try_count_wildcard(word, wildcard)
+    .expect("anagram count overflowed u128, use `count_wildcard_big` instead")
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards after normalizing it with the given options
+pub fn count_wildcard_with(word: &str, wildcard: &Wildcard, options: &AnagramOptions) -> u128 {
+  try_count_wildcard_with(word, wildcard, options)
+    .expect("anagram count overflowed u128, use `count_wildcard_big` instead")
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards, returning `None` if the count does not fit in a `u128`
+pub fn try_count_wildcard(word: &str, wildcard: &Wildcard) -> Option<u128> {
+  count_wildcard_big(word, wildcard).to_u128()
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards after normalizing it with the given options, returning `None` on
+/// overflow
+pub fn try_count_wildcard_with(
+  word: &str,
+  wildcard: &Wildcard,
+  options: &AnagramOptions,
+) -> Option<u128> {
+  count_wildcard_big_with(word, wildcard, options).to_u128()
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards, exactly
+pub fn count_wildcard_big(word: &str, wildcard: &Wildcard) -> BigUint {
+  count_wildcard_big_with(word, wildcard, &AnagramOptions::new().case(Case::Lower))
+}
+
+/// Count the number of distinct strings that can be formed from a word with
+/// wildcards after normalizing it with the given options, exactly
+///
+/// Wildcards are taken out of the word before it is normalized, as in
+/// `is_anagram_wildcard_with`
+pub fn count_wildcard_big_with(
+  word: &str,
+  wildcard: &Wildcard,
+  options: &AnagramOptions,
+) -> BigUint {
+  let (blanks, rest) = wildcard.split(word);
+  let rest = options.normalize(&rest);
+
+  let mut counts: HashMap<&str, usize> = HashMap::new();
+  for unit in options.units(&rest) {
+    *counts.entry(unit).or_insert(0) += 1;
+  }
+
+  // units no wildcard can become are arranged the same way every time
+  let fixed: Vec<usize> = counts
+    .iter()
+    .filter(|(unit, _)| wildcard.matches(unit).is_none())
+    .map(|(_, &k)| k)
+    .collect();
+
+  // ways[j] counts arrangements of the letters seen so far when j wildcards
+  // have been assigned to them
+  let mut ways = vec![BigUint::zero(); blanks + 1];
+  ways[0] = multinomial_big(&fixed);
+  let mut len: usize = fixed.iter().sum();
+
+  for c in &wildcard.alphabet {
+    let k = counts
+      .get(c.encode_utf8(&mut [0; 4]) as &str)
+      .copied()
+      .unwrap_or(0);
+    let mut next = vec![BigUint::zero(); blanks + 1];
+
+    for (used, ways) in ways.iter().enumerate().filter(|(_, ways)| !ways.is_zero()) {
+      for extra in 0..=blanks - used {
+        // interleave k + extra copies of c with the letters placed so far
+        next[used + extra] += ways * binomial(len + used + k + extra, k + extra);
+      }
+    }
+
+    ways = next;
+    len += k;
+  }
+
+  ways.pop().unwrap()
+}
+
+/// Compute `n` choose `k` exactly
+fn binomial(n: usize, k: usize) -> BigUint {
+  multinomial_big(&[k, n - k])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::count;
+
+  #[test]
+  fn test_is_anagram_wildcard() {
+    let wildcard = Wildcard::new('?');
+    assert_eq!(
+      is_anagram_wildcard("re?t", "tree", &wildcard),
+      Some(vec!['e'])
+    );
+    assert_eq!(
+      is_anagram_wildcard("re?t", "Rent", &wildcard),
+      Some(vec!['n'])
+    );
+    assert_eq!(is_anagram_wildcard("re?t", "rest!", &wildcard), None);
+    assert_eq!(is_anagram_wildcard("re?t", "ret", &wildcard), None);
+    assert_eq!(is_anagram_wildcard("re?t", "tr1e", &wildcard), None);
+    assert_eq!(is_anagram_wildcard("re?t??", "bitten", &wildcard), None);
+    assert_eq!(
+      is_anagram_wildcard("re?t??", "better", &wildcard),
+      Some(vec!['b', 'e', 't'])
+    );
+    assert_eq!(
+      is_anagram_wildcard("re?t??", "tester", &wildcard),
+      Some(vec!['e', 's', 't'])
+    );
+    assert_eq!(
+      is_anagram_wildcard("??", "ab", &wildcard),
+      Some(vec!['a', 'b'])
+    );
+    assert_eq!(is_anagram_wildcard("ab", "ba", &wildcard), Some(vec![]));
+    assert_eq!(
+      is_anagram_wildcard("a_", "ab", &Wildcard::new('_').alphabet("ac".chars())),
+      None
+    );
+  }
+
+  #[test]
+  fn test_is_anagram_wildcard_with() {
+    assert_eq!(
+      is_anagram_wildcard_with(
+        "Dirty ro?m",
+        "Dormitory",
+        &Wildcard::new('?'),
+        &AnagramOptions::phrase()
+      ),
+      Some(vec!['o'])
+    );
+  }
+
+  #[test]
+  fn test_count_wildcard() {
+    let wildcard = Wildcard::new('?').alphabet("ab".chars());
+    assert_eq!(count_wildcard("a?", &wildcard), 3);
+    assert_eq!(count_wildcard("??", &wildcard), 4);
+    assert_eq!(count_wildcard("1?", &wildcard), 4);
+    assert_eq!(count_wildcard("", &wildcard), 1);
+    assert_eq!(count_wildcard("?", &Wildcard::new('?').alphabet(None)), 0);
+    assert_eq!(
+      count_wildcard("ordeals", &Wildcard::new('?')),
+      count("ordeals")
+    );
+    assert_eq!(count_wildcard("?", &Wildcard::new('?')), 26);
+    assert_eq!(count_wildcard("???", &Wildcard::new('?')), 26 * 26 * 26);
+    // "ab" plus one letter: 3! arrangements for the 24 new letters, 3 each
+    // for "aab" and "abb"
+    assert_eq!(count_wildcard("ab?", &Wildcard::new('?')), 24 * 6 + 3 + 3);
+    assert_eq!(count_wildcard("A?", &wildcard), 3);
+  }
+
+  #[test]
+  fn test_count_wildcard_with() {
+    let wildcard = Wildcard::new('?').alphabet("ab".chars());
+    assert_eq!(
+      count_wildcard_with("A?", &wildcard, &AnagramOptions::new()),
+      4
+    );
+    assert_eq!(
+      count_wildcard_with("A, ?", &wildcard, &AnagramOptions::phrase()),
+      3
+    );
+    assert_eq!(
+      count_wildcard_with(
+        "e\u{301}?",
+        &Wildcard::new('?'),
+        &AnagramOptions::new().graphemes(true)
+      ),
+      26 * 2
+    );
+  }
+
+  #[test]
+  fn test_count_wildcard_big() {
+    let word = "abcdefghijklmnopqrstuvwxyz?????????";
+    assert_eq!(try_count_wildcard(word, &Wildcard::new('?')), None);
+    assert!(count_wildcard_big(word, &Wildcard::new('?')) > BigUint::from(u128::MAX));
+  }
+}
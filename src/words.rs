@@ -0,0 +1,455 @@
+use crate::{
+  count_arrangements_big, next_permutation, prev_permutation, try_count_arrangements,
+  AnagramOptions,
+};
+use num_bigint::BigUint;
+use std::{
+  collections::HashMap,
+  fmt::{self, Debug, Formatter},
+  hash::Hash,
+  iter::FusedIterator,
+  sync::Arc,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A function that splits text into words
+type Tokenize = dyn Fn(&str) -> Vec<&str> + Send + Sync;
+
+/// Splits text into the words that word-level functions permute and compare
+#[derive(Clone, Default)]
+pub enum Tokenizer {
+  /// Split on runs of whitespace
+  #[default]
+  Whitespace,
+  /// Split on Unicode word boundaries, dropping punctuation and whitespace
+  Words,
+  /// Split with a custom function
+  Custom(Arc<Tokenize>),
+}
+
+impl Tokenizer {
+  /// Create a tokenizer from a custom function
+  ///
+  /// Examples:
+  /// Tokenizer::custom(|text| text.split(',').collect())
+  pub fn custom<F>(tokenize: F) -> Self
+  where
+    F: Fn(&str) -> Vec<&str> + Send + Sync + 'static,
+  {
+    Tokenizer::Custom(Arc::new(tokenize))
+  }
+
+  /// Split text into words
+  pub fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+    match self {
+      Tokenizer::Whitespace => text.split_whitespace().collect(),
+      Tokenizer::Words => text.unicode_words().collect(),
+      Tokenizer::Custom(tokenize) => tokenize(text),
+    }
+  }
+}
+
+impl Debug for Tokenizer {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Tokenizer::Whitespace => write!(f, "Whitespace"),
+      Tokenizer::Words => write!(f, "Words"),
+      Tokenizer::Custom(_) => write!(f, "Custom(..)"),
+    }
+  }
+}
+
+/// Split text into words and normalize each one with the given options,
+/// dropping the words that normalize to nothing
+fn normalized_words(text: &str, tokenizer: &Tokenizer, options: &AnagramOptions) -> Vec<String> {
+  tokenizer
+    .tokenize(text)
+    .into_iter()
+    .map(|word| options.normalize(word))
+    .filter(|word| !word.is_empty())
+    .collect()
+}
+
+/// Check if two lists contain the same items the same number of times
+fn same_words<W: Eq + Hash>(left: Vec<W>, right: Vec<W>) -> bool {
+  let mut counts: HashMap<W, i64> = HashMap::new();
+
+  for word in left {
+    *counts.entry(word).or_insert(0) += 1;
+  }
+
+  for word in right {
+    *counts.entry(word).or_insert(0) -= 1;
+  }
+
+  counts.values().all(|&count| count == 0)
+}
+
+/// Step words to their next ordering, or `None` if there is none
+fn step_words<W: Ord>(mut words: Vec<W>, step: fn(&mut [W]) -> bool) -> Option<Vec<W>> {
+  if step(&mut words) {
+    Some(words)
+  } else {
+    None
+  }
+}
+
+/// Check if two sentences contain the same words the same number of times,
+/// in any order
+///
+/// Examples:
+/// ("the cat sat", "sat the cat") -> true
+/// ("the cat sat", "the cat sat sat") -> false
+pub fn is_word_anagram(left: &str, right: &str, tokenizer: &Tokenizer) -> bool {
+  same_words(tokenizer.tokenize(left), tokenizer.tokenize(right))
+}
+
+/// Check if two sentences contain the same words the same number of times,
+/// in any order, after normalizing each word with the given options
+///
+/// Examples:
+/// ("The cat", "cat the") with `Case::Lower` -> true
+pub fn is_word_anagram_with(
+  left: &str,
+  right: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> bool {
+  same_words(
+    normalized_words(left, tokenizer, options),
+    normalized_words(right, tokenizer, options),
+  )
+}
+
+/// Count the distinct orderings of the words of a sentence
+///
+/// Panics if the count does not fit in a `u128`, see
+/// `try_count_word_orderings` and `count_word_orderings_big` for long
+/// sentences
+/// Examples:
+/// "to be or not to be" -> 180
+pub fn count_word_orderings(text: &str, tokenizer: &Tokenizer) -> u128 {
+  try_count_word_orderings(text, tokenizer)
+    .expect("ordering count overflowed u128, use `count_word_orderings_big` instead")
+}
+
+/// Count the distinct orderings of the words of a sentence after normalizing
+/// each word with the given options
+///
+/// Panics if the count does not fit in a `u128`, see
+/// `try_count_word_orderings_with` and `count_word_orderings_big_with` for
+/// long sentences
+/// Examples:
+/// "To be or not to be" with `Case::Lower` -> 180
+pub fn count_word_orderings_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> u128 {
+  try_count_word_orderings_with(text, tokenizer, options)
+    .expect("ordering count overflowed u128, use `count_word_orderings_big_with` instead")
+}
+
+/// Count the distinct orderings of the words of a sentence, returning `None`
+/// if the count does not fit in a `u128`
+pub fn try_count_word_orderings(text: &str, tokenizer: &Tokenizer) -> Option<u128> {
+  try_count_arrangements(tokenizer.tokenize(text))
+}
+
+/// Count the distinct orderings of the words of a sentence after normalizing
+/// each word with the given options, returning `None` if the count does not
+/// fit in a `u128`
+pub fn try_count_word_orderings_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> Option<u128> {
+  try_count_arrangements(normalized_words(text, tokenizer, options))
+}
+
+/// Count the distinct orderings of the words of a sentence, exactly
+pub fn count_word_orderings_big(text: &str, tokenizer: &Tokenizer) -> BigUint {
+  count_arrangements_big(tokenizer.tokenize(text))
+}
+
+/// Count the distinct orderings of the words of a sentence after normalizing
+/// each word with the given options, exactly
+pub fn count_word_orderings_big_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> BigUint {
+  count_arrangements_big(normalized_words(text, tokenizer, options))
+}
+
+/// Get the next lexicographically greater ordering of the words of a
+/// sentence, or `None` if they are already in the greatest order
+///
+/// Examples:
+/// "a b c" -> ["a", "c", "b"]
+/// "c b a" -> None
+pub fn next_word_ordering<'a>(text: &'a str, tokenizer: &Tokenizer) -> Option<Vec<&'a str>> {
+  step_words(tokenizer.tokenize(text), next_permutation)
+}
+
+/// Get the next lexicographically greater ordering of the words of a
+/// sentence after normalizing each word with the given options, or `None` if
+/// they are already in the greatest order
+pub fn next_word_ordering_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> Option<Vec<String>> {
+  step_words(normalized_words(text, tokenizer, options), next_permutation)
+}
+
+/// Get the previous lexicographically smaller ordering of the words of a
+/// sentence, or `None` if they are already in the smallest order
+pub fn prev_word_ordering<'a>(text: &'a str, tokenizer: &Tokenizer) -> Option<Vec<&'a str>> {
+  step_words(tokenizer.tokenize(text), prev_permutation)
+}
+
+/// Get the previous lexicographically smaller ordering of the words of a
+/// sentence after normalizing each word with the given options, or `None` if
+/// they are already in the smallest order
+pub fn prev_word_ordering_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> Option<Vec<String>> {
+  step_words(normalized_words(text, tokenizer, options), prev_permutation)
+}
+
+/// Iterate over the orderings of the words of a sentence, starting with the
+/// given ordering and stepping to each lexicographically greater one
+///
+/// Every distinct ordering is visited once, so starting from the words in
+/// sorted order visits all of them
+/// Examples:
+/// "b a c" -> "b a c", "b c a", "c a b", "c b a"
+pub fn word_orderings<'a>(text: &'a str, tokenizer: &Tokenizer) -> WordOrderings<&'a str> {
+  WordOrderings::new(tokenizer.tokenize(text), next_permutation)
+}
+
+/// Iterate over the orderings of the words of a sentence after normalizing
+/// each word with the given options, stepping to each lexicographically
+/// greater one
+pub fn word_orderings_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> WordOrderings<String> {
+  WordOrderings::new(normalized_words(text, tokenizer, options), next_permutation)
+}
+
+/// Iterate over the orderings of the words of a sentence, starting with the
+/// given ordering and stepping to each lexicographically smaller one
+pub fn word_orderings_rev<'a>(text: &'a str, tokenizer: &Tokenizer) -> WordOrderings<&'a str> {
+  WordOrderings::new(tokenizer.tokenize(text), prev_permutation)
+}
+
+/// Iterate over the orderings of the words of a sentence after normalizing
+/// each word with the given options, stepping to each lexicographically
+/// smaller one
+pub fn word_orderings_rev_with(
+  text: &str,
+  tokenizer: &Tokenizer,
+  options: &AnagramOptions,
+) -> WordOrderings<String> {
+  WordOrderings::new(normalized_words(text, tokenizer, options), prev_permutation)
+}
+
+/// An iterator over orderings of the words of a sentence, created by
+/// [`word_orderings`] and [`word_orderings_rev`], or their `_with` versions
+/// which yield normalized words
+#[derive(Clone)]
+pub struct WordOrderings<W> {
+  words: Vec<W>,
+  step:  fn(&mut [W]) -> bool,
+  first: bool,
+  done:  bool,
+}
+
+impl<W> WordOrderings<W> {
+  fn new(words: Vec<W>, step: fn(&mut [W]) -> bool) -> Self {
+    Self {
+      words,
+      step,
+      first: true,
+      done: false,
+    }
+  }
+}
+
+impl<W: Debug> Debug for WordOrderings<W> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_struct("WordOrderings")
+      .field("words", &self.words)
+      .field("done", &self.done)
+      .finish()
+  }
+}
+
+impl<W: Clone> Iterator for WordOrderings<W> {
+  type Item = Vec<W>;
+
+  fn next(&mut self) -> Option<Vec<W>> {
+    if self.done {
+      return None;
+    }
+
+    if self.first {
+      self.first = false;
+    } else if !(self.step)(&mut self.words) {
+      self.done = true;
+      return None;
+    }
+
+    Some(self.words.clone())
+  }
+}
+
+impl<W: Clone> FusedIterator for WordOrderings<W> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Case;
+
+  #[test]
+  fn test_tokenize() {
+    assert_eq!(Tokenizer::Whitespace.tokenize(" the  cat\tsat\n"), [
+      "the", "cat", "sat"
+    ]);
+    assert_eq!(Tokenizer::Words.tokenize("The cat, sat!"), [
+      "The", "cat", "sat"
+    ]);
+    assert_eq!(
+      Tokenizer::custom(|text| text.split(',').collect()).tokenize("a,b,,c"),
+      ["a", "b", "", "c"]
+    );
+    assert_eq!(Tokenizer::default().tokenize(""), Vec::<&str>::new());
+  }
+
+  #[test]
+  fn test_is_word_anagram() {
+    let tokenizer = Tokenizer::Whitespace;
+    assert!(is_word_anagram("the cat sat", "sat  the cat", &tokenizer));
+    assert!(!is_word_anagram(
+      "the cat sat",
+      "the cat sat sat",
+      &tokenizer
+    ));
+    assert!(!is_word_anagram("the cat sat", "the act sat", &tokenizer));
+    assert!(!is_word_anagram("the cat sat", "sat, the cat", &tokenizer));
+    assert!(is_word_anagram(
+      "the cat sat",
+      "sat, the cat",
+      &Tokenizer::Words
+    ));
+    assert!(is_word_anagram("", " ", &tokenizer));
+  }
+
+  #[test]
+  fn test_count_word_orderings() {
+    let tokenizer = Tokenizer::Whitespace;
+    assert_eq!(count_word_orderings("to be or not to be", &tokenizer), 180);
+    assert_eq!(count_word_orderings("", &tokenizer), 1);
+    assert_eq!(
+      count_word_orderings_big("to be or not to be", &tokenizer),
+      BigUint::from(180u32)
+    );
+
+    let long = (0..40).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+    assert_eq!(try_count_word_orderings(&long, &tokenizer), None);
+  }
+
+  #[test]
+  fn test_next_prev_word_ordering() {
+    let tokenizer = Tokenizer::Whitespace;
+    assert_eq!(
+      next_word_ordering("a b c", &tokenizer),
+      Some(vec!["a", "c", "b"])
+    );
+    assert_eq!(next_word_ordering("c b a", &tokenizer), None);
+    assert_eq!(
+      prev_word_ordering("a c b", &tokenizer),
+      Some(vec!["a", "b", "c"])
+    );
+    assert_eq!(prev_word_ordering("a b c", &tokenizer), None);
+    assert_eq!(next_word_ordering("the cat", &Tokenizer::Words), None);
+  }
+
+  #[test]
+  fn test_word_orderings() {
+    let tokenizer = Tokenizer::Whitespace;
+    let orderings: Vec<_> = word_orderings("b a c", &tokenizer).collect();
+    assert_eq!(orderings, [
+      ["b", "a", "c"],
+      ["b", "c", "a"],
+      ["c", "a", "b"],
+      ["c", "b", "a"]
+    ]);
+
+    assert_eq!(word_orderings("a a b b", &tokenizer).count(), 6);
+    assert_eq!(word_orderings_rev("b a c", &tokenizer).count(), 3);
+    assert_eq!(word_orderings("", &tokenizer).collect::<Vec<_>>(), [Vec::<
+      &str,
+    >::new(
+    )]);
+  }
+
+  #[test]
+  fn test_word_functions_with() {
+    let tokenizer = Tokenizer::Whitespace;
+    let lower = AnagramOptions::new().case(Case::Lower);
+    assert!(!is_word_anagram("The cat", "cat the", &tokenizer));
+    assert!(is_word_anagram_with(
+      "The cat", "cat the", &tokenizer, &lower
+    ));
+    assert!(is_word_anagram_with(
+      "The cat!",
+      "cat, the",
+      &tokenizer,
+      &AnagramOptions::phrase()
+    ));
+    assert!(is_word_anagram_with(
+      "the cat -",
+      "cat the",
+      &tokenizer,
+      &AnagramOptions::phrase()
+    ));
+
+    assert_eq!(count_word_orderings("To be or not to be", &tokenizer), 360);
+    assert_eq!(
+      count_word_orderings_with("To be or not to be", &tokenizer, &lower),
+      180
+    );
+    assert_eq!(
+      count_word_orderings_big_with("To be or not to be", &tokenizer, &lower),
+      BigUint::from(180u32)
+    );
+    assert_eq!(
+      try_count_word_orderings_with("To be", &tokenizer, &lower),
+      Some(2)
+    );
+
+    assert_eq!(
+      next_word_ordering_with("A b c", &tokenizer, &lower),
+      Some(vec!["a".to_string(), "c".into(), "b".into()])
+    );
+    assert_eq!(next_word_ordering_with("C B a", &tokenizer, &lower), None);
+    assert_eq!(
+      prev_word_ordering_with("a C b", &tokenizer, &lower),
+      Some(vec!["a".to_string(), "b".into(), "c".into()])
+    );
+
+    let orderings: Vec<_> = word_orderings_with("B a A", &tokenizer, &lower).collect();
+    assert_eq!(orderings, [["b", "a", "a"]]);
+    assert_eq!(word_orderings_with("A a b", &tokenizer, &lower).count(), 3);
+    assert_eq!(
+      word_orderings_rev_with("B a c", &tokenizer, &lower).count(),
+      3
+    );
+  }
+}